@@ -1,53 +1,88 @@
 extern crate clap;
 extern crate rand;
 extern crate serde;
-extern crate regex;
 extern crate ansi_term;
 extern crate dirs;
+extern crate termion;
+extern crate unicode_normalization;
 
 use std::iter::Iterator;
 use std::io::{BufRead,Write};
 use std::path::{Path,PathBuf};
 use std::process::exit;
 use std::fs;
+use std::time::Instant;
+use std::collections::{BTreeMap,HashMap,HashSet};
 use clap::{App, Arg, SubCommand};
-use regex::Regex;
 use rand::{thread_rng,Rng};
 use ansi_term::Colour::{Red,Green, Blue};
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use unicode_normalization::UnicodeNormalization;
 use vocajeux::*;
 
 ///Flashcards
-fn flashcards(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, phon: bool, filtertags: Option<&Vec<&str>>) {
-    let instructions = "type ENTER to turn, q to quit, k for correct, i for incorrect";
+#[allow(clippy::too_many_arguments)]
+fn flashcards(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, fields: &FieldPair, template: Option<&CardTemplate>, phon: bool, filtertags: Option<&Vec<&str>>, session: &mut Session) {
+    let instructions = "type ENTER to turn, q to quit, grade with 1 (again), 2 (hard), 3 (good) or 4 (easy); k and i are aliases for good and again; S to suspend, B to bury, K to mark as known, U to undo the last answer";
     println!("FLASHCARDS ({})", instructions);
     println!("---------------------------------------------------------------------------------------");
-    loop {
-        //select a random item
-        let vocaitem;
+    let mut history: Vec<Answer> = Vec::new();
+    let mut redo: Option<&VocaItem> = None;
+    while !session.done() {
+        //select a random item, or the one of the undone answer
+        let vocaitem = match redo.take().or_else(|| data.pickwith(optscoredata.as_deref_mut(), filtertags, false, |item| fields.applies(item))) {
+            Some(vocaitem) => vocaitem,
+            None => {
+                eprintln!("No items left to practise");
+                return;
+            }
+        };
+        let before = Answer::new(vocaitem, optscoredata.as_deref(), session);
         if let Some(ref mut scoredata) = optscoredata {
-            vocaitem = data.pick(Some(scoredata), filtertags, true);
-        } else {
-            vocaitem = data.pick(None, filtertags, true);
+            scoredata.seen(vocaitem);
         }
+        let showexample = fields.prompt != "example" && fields.answer != "example";
         let mut turned = false;
-        let correct;
+        let mut grade = None;
+        //the time it takes to recall the answer is measured until the card is first turned
+        let shown = Instant::now();
+        let mut recalltime: Option<u64> = None;
         loop{
-            if turned {
-                println!("{}", vocaitem.transcription);
-                println!("{}", vocaitem.translation);
-                println!("{}", vocaitem.example);
+            if let Some(template) = template {
+                if turned {
+                    println!("{}", template.back(vocaitem));
+                } else {
+                    println!("{}", template.front(vocaitem));
+                }
+            } else if turned {
+                if fields.prompt != "transcription" && fields.answer != "transcription" {
+                    println!("{}", vocaitem.transcription);
+                }
+                println!("{}", fields.answer(vocaitem));
+                if showexample {
+                    println!("{}", vocaitem.plainexample());
+                }
             } else {
-                quizprompt(vocaitem, phon);
-                println!("{}", vocaitem.example);
+                fieldprompt(vocaitem, fields, phon);
+                if showexample {
+                    println!("{}", vocaitem.plainexample());
+                }
             }
             //get response from user
-            if let Some(response) = getinputline() {
-                if response == "i" {
-                    correct = false;
+            if let Some(response) = getinputkey() {
+                if let Some(message) = optscoredata.as_deref_mut().and_then(|scoredata| excludeitem(&response, vocaitem, scoredata)) {
+                    println!("{}", Blue.paint(message));
                     break;
-                } else if response == "k" {
-                    correct = true;
+                } else if let Some(response) = Grade::parse(&response) {
+                    grade = Some(response);
                     break;
+                } else if response == "U" {
+                    redo = undolast(&mut history, optscoredata.as_deref_mut(), session);
+                    if redo.is_some() {
+                        break;
+                    }
                 } else if response == "h" {
                     println!("{}",instructions);
                 } else if response == "q" {
@@ -57,56 +92,715 @@ fn flashcards(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, phon: b
                 }
             } else {
                 turned = !turned;
+                recalltime.get_or_insert(shown.elapsed().as_millis() as u64);
             }
         }
+        let grade = match grade {
+            Some(grade) => grade,
+            None => {
+                println!();
+                continue;
+            }
+        };
         if let Some(ref mut scoredata) = optscoredata {
-            scoredata.addscore(&vocaitem, correct);
+            scoredata.addtimedgrade(vocaitem, grade, recalltime.unwrap_or(shown.elapsed().as_millis() as u64));
         }
+        session.record(vocaitem, grade.correct(), optscoredata.as_deref());
+        history.push(before);
         println!();
     }
 }
 
+///Takes an item out of practice: S suspends it, B buries it until tomorrow and K marks it as
+///known. Returns a message if the response was such a command.
+fn excludeitem(response: &str, vocaitem: &VocaItem, scoredata: &mut VocaScore) -> Option<&'static str> {
+    match response {
+        "S" => {
+            scoredata.suspend(vocaitem);
+            Some("Suspended (use vj restore to bring it back)")
+        },
+        "B" => {
+            scoredata.bury(vocaitem, (ReviewStats::today() + 1) * 86400);
+            Some("Buried until tomorrow")
+        },
+        "K" => {
+            scoredata.markknown(vocaitem);
+            Some("Marked as known")
+        },
+        _ => None
+    }
+}
+
+///Parses an exclusion command in a game that shows several items at once, where the command
+///names the item it applies to: S, B or K, a space and e.g. the number of the item (S 2)
+fn parseexclusion(response: &str) -> Option<(&str, &str)> {
+    let (command, target) = response.split_once(' ')?;
+    match command {
+        "S" | "B" | "K" if !target.trim().is_empty() => Some((command, target.trim())),
+        _ => None
+    }
+}
+
+///The state before an answer was recorded, so the answer can be undone
+struct Answer<'a> {
+    vocaitem: &'a VocaItem,
+    scoredata: Option<VocaScore>,
+    session: Session,
+}
+
+impl<'a> Answer<'a> {
+    ///Remember the state before answering, this must be called before anything is recorded for
+    ///the item
+    fn new(vocaitem: &'a VocaItem, optscoredata: Option<&VocaScore>, session: &Session) -> Answer<'a> {
+        Answer {
+            vocaitem,
+            scoredata: optscoredata.map(|scoredata| scoredata.snapshot(vocaitem)),
+            session: session.clone(),
+        }
+    }
+
+    ///Undo the answer: restores the scores of the item and the session
+    fn undo(self, optscoredata: Option<&mut VocaScore>, session: &mut Session) -> &'a VocaItem {
+        if let (Some(scoredata), Some(before)) = (optscoredata, self.scoredata) {
+            scoredata.updateitem(&before, self.vocaitem);
+        }
+        *session = self.session;
+        self.vocaitem
+    }
+}
+
+///Undo the last answer of the session (U), returns the item so it can be asked again
+fn undolast<'a>(history: &mut Vec<Answer<'a>>, optscoredata: Option<&mut VocaScore>, session: &mut Session) -> Option<&'a VocaItem> {
+    match history.pop() {
+        Some(answer) => {
+            let vocaitem = answer.undo(optscoredata, session);
+            println!("{}", Blue.paint(format!("Undid the answer for {}, try again", vocaitem.word)));
+            Some(vocaitem)
+        },
+        None => {
+            println!("{}", Red.paint("Nothing to undo"));
+            None
+        }
+    }
+}
+
+///Asks a yes/no question, ENTER (or the end of the input) gives the default answer
+fn confirm(question: &str, default: bool) -> bool {
+    print!("{} [{}] ", question, if default { "Y/n" } else { "y/N" });
+    std::io::stdout().flush().unwrap();
+    let mut line = String::new();
+    match std::io::stdin().read_line(&mut line) {
+        Ok(_) => match line.trim().to_lowercase().as_str() {
+            "y" | "yes" => true,
+            "n" | "no" => false,
+            _ => default
+        },
+        Err(_) => default
+    }
+}
+
 fn getinputline() -> Option<String> {
     print!(">>> ");
     std::io::stdout().flush().unwrap();
     let stdin = std::io::stdin();
     let response = stdin.lock().lines().next().unwrap().unwrap(); //read one line only
-    if response != "" {
-        return Some(response);
+    if !response.is_empty() {
+        Some(response)
     } else {
-        return None;
+        None
     }
 }
 
+///Reads a single keypress without waiting for ENTER, returns None if ENTER was pressed. Falls
+///back to reading a whole line if standard input is not a terminal.
+fn getinputkey() -> Option<String> {
+    if !termion::is_tty(&std::io::stdin()) {
+        return getinputline();
+    }
+    print!(">>> ");
+    std::io::stdout().flush().unwrap();
+    let key = match std::io::stdout().into_raw_mode() {
+        Ok(_rawterminal) => { //raw mode lasts as long as this is in scope
+            std::io::stdin().keys().next()
+        },
+        Err(_) => return getinputline()
+    };
+    let response = match key {
+        Some(Ok(Key::Char('\n'))) | None => None,
+        Some(Ok(Key::Char(c))) => Some(c.to_string()),
+        Some(Ok(Key::Ctrl('c'))) | Some(Ok(Key::Ctrl('d'))) | Some(Ok(Key::Esc)) => Some("q".to_string()),
+        Some(Ok(_)) | Some(Err(_)) => Some(String::new()), //unsupported key, counts as invalid input
+    };
+    println!("{}", response.as_deref().unwrap_or(""));
+    response
+}
 
-fn checktranslation(input: &String, reference: &String) -> bool {
-    for candidate in  Regex::new(r"\b[\w\s]+\b").unwrap().find_iter(reference) {
-        let candidate = candidate.as_str().to_lowercase();
-        if candidate == input.to_lowercase() {
-            return true;
+///Reads a match quiz response. A single match (a digit and a letter) is taken as soon as both
+///keys are pressed; if a separator follows the first key the rest is read up to ENTER, so a match
+///can be given as 1 a and several matches can be given at once. Falls back to reading a whole
+///line if there are too many pairs to fit in a single digit.
+fn getmatchinput(matchcount: usize) -> Option<String> {
+    if matchcount > 9 || !termion::is_tty(&std::io::stdin()) {
+        return getinputline();
+    }
+    print!(">>> ");
+    std::io::stdout().flush().unwrap();
+    let rawterminal = match std::io::stdout().into_raw_mode() {
+        Ok(rawterminal) => rawterminal, //raw mode lasts as long as this is in scope
+        Err(_) => return getinputline()
+    };
+    let mut response = String::new();
+    for key in std::io::stdin().keys() {
+        match key {
+            Ok(Key::Char('\n')) => break,
+            Ok(Key::Ctrl('c')) | Ok(Key::Ctrl('d')) | Ok(Key::Esc) => {
+                response = "q".to_string();
+                break;
+            },
+            Ok(Key::Backspace) => {
+                if response.pop().is_some() {
+                    print!("\u{8} \u{8}");
+                }
+            },
+            Ok(Key::Char(c)) => {
+                response.push(c);
+                print!("{}", c);
+            },
+            Ok(_) | Err(_) => {}
+        }
+        std::io::stdout().flush().unwrap();
+        let keys: Vec<char> = response.chars().collect();
+        //the first key may be either the number or the letter, or S, B or K to exclude an item
+        let startsmatch = keys.first().map(|c| c.is_ascii_digit() || "SBK".contains(*c) || parsematchlabel(&c.to_string()).map(|index| index < matchcount).unwrap_or(false)).unwrap_or(true);
+        //after an upper case S, B or K the rest is always read up to ENTER, as it may be a command
+        if !startsmatch || (keys.len() == 2 && keys[1].is_alphanumeric() && !"SBK".contains(keys[0])) {
+            break;
         }
     }
-    false
+    drop(rawterminal);
+    println!();
+    if response.is_empty() {
+        None
+    } else {
+        Some(response)
+    }
 }
 
 fn quizprompt(vocaitem: &VocaItem, phon: bool) {
-    if phon {
-        println!("{}: {} ({})", Blue.paint("Translate"), vocaitem, vocaitem.transcription);
+    fieldprompt(vocaitem, &FieldPair::default(), phon)
+}
+
+///Prompts for the answer field of an item, given its prompt field
+fn fieldprompt(vocaitem: &VocaItem, fields: &FieldPair, phon: bool) {
+    let label = if fields.is_default() {
+        "Translate".to_string()
     } else {
-        println!("{}: {}", Blue.paint("Translate"), vocaitem);
+        format!("Give the {} for the {}", fields.answer, fields.prompt)
+    };
+    if phon && fields.prompt != "transcription" && fields.answer != "transcription" {
+        println!("{}: {} ({})", Blue.paint(label), fields.prompt(vocaitem), vocaitem.transcription);
+    } else {
+        println!("{}: {}", Blue.paint(label), fields.prompt(vocaitem));
     }
 }
 
-///Picks and prints a random item, provides no further interaction
-fn pick(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, phon: bool, translation: bool, example: bool, filtertags: Option<&Vec<&str>>) {
-    //select a random item
-    let vocaitem;
+///Hangman: guess the word letter by letter, with the translation as a clue. Unless diacritics
+///are strict, guessing a letter also reveals its accented variants (e.g. e reveals é and è).
+fn hangman(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, phon: bool, strictdiacritics: bool, filtertags: Option<&Vec<&str>>, session: &mut Session) {
+    //commands start with ! so that no letter is taken as a command
+    let instructions = "guess a letter or the whole word, !p for phonetic transcription, !q to quit, ENTER to give up; !s to suspend, !b to bury, !k to mark as known, !u to undo the last answer";
+    println!("HANGMAN ({})", instructions);
+    println!("---------------------------------------------------------------------------------");
+    let maxmistakes = 7;
+    let normalise = |s: &str| -> String {
+        let s: String = s.nfc().collect::<String>().to_lowercase();
+        if strictdiacritics { s } else { stripdiacritics(&s) }
+    };
+    let mut history: Vec<Answer> = Vec::new();
+    let mut redo: Option<&VocaItem> = None;
+    while !session.done() {
+        //select a random item that has letters to guess, or the one of the undone answer
+        let vocaitem = match redo.take().or_else(|| data.pickwith(optscoredata.as_deref_mut(), filtertags, false, |item| item.word.chars().any(|c| c.is_alphabetic()))) {
+            Some(vocaitem) => vocaitem,
+            None => {
+                eprintln!("No items left to practise");
+                return;
+            }
+        };
+        let before = Answer::new(vocaitem, optscoredata.as_deref(), session);
+        if let Some(ref mut scoredata) = optscoredata {
+            scoredata.seen(vocaitem);
+        }
+        let letters: Vec<char> = vocaitem.word.nfc().collect();
+        //anything that isn't a letter (spaces, hyphens, apostrophes) is revealed from the start
+        let mut revealed: Vec<bool> = letters.iter().map(|c| !c.is_alphabetic()).collect();
+        let mut guessed: Vec<String> = Vec::new();
+        let mut mistakes = 0;
+        if phon {
+            println!("{}: {} ({})", Blue.paint("Clue"), vocaitem.translation, vocaitem.transcription);
+        } else {
+            println!("{}: {}", Blue.paint("Clue"), vocaitem.translation);
+        }
+        let asked = Instant::now();
+        let mut correct = false;
+        let mut skipped = false; //the item was excluded or an earlier answer was undone
+        while mistakes < maxmistakes {
+            let pattern: Vec<String> = letters.iter().zip(revealed.iter()).map(|(c, &shown)| if shown { c.to_string() } else { "_".to_string() }).collect();
+            println!("{}    (mistakes: {}/{}{}{})", pattern.join(" "), mistakes, maxmistakes, if guessed.is_empty() { "" } else { ", guessed: " }, guessed.join(" "));
+            let response = match getinputline() {
+                Some(response) => response.trim().to_string(),
+                None => break
+            };
+            if response == "!p" {
+                println!("{}", vocaitem.transcription);
+                continue;
+            } else if response == "!q" {
+                return;
+            } else if response == "!h" || response == "?" {
+                println!("{}",instructions);
+                continue;
+            } else if response == "!u" {
+                redo = undolast(&mut history, optscoredata.as_deref_mut(), session);
+                if redo.is_some() {
+                    skipped = true;
+                    break;
+                }
+                continue;
+            } else if let Some(message) = response.strip_prefix('!').zip(optscoredata.as_deref_mut()).and_then(|(command, scoredata)| excludeitem(&command.to_uppercase(), vocaitem, scoredata)) {
+                println!("{}", Blue.paint(message));
+                skipped = true;
+                break;
+            } else if response.chars().count() > 1 {
+                //guess of the whole word
+                if normalise(&response) == normalise(&vocaitem.word) {
+                    correct = true;
+                    break;
+                }
+                println!("{}", Red.paint("Incorrect!"));
+                mistakes += 1;
+            } else {
+                let guess = normalise(&response);
+                if guessed.contains(&guess) {
+                    println!("You already guessed {}", guess);
+                    continue;
+                }
+                guessed.push(guess.clone());
+                let mut found = false;
+                for (i, c) in letters.iter().enumerate() {
+                    if !revealed[i] && normalise(&c.to_string()) == guess {
+                        revealed[i] = true;
+                        found = true;
+                    }
+                }
+                if !found {
+                    println!("{} No {} in this word", Red.paint("Incorrect!"), guess);
+                    mistakes += 1;
+                } else if revealed.iter().all(|shown| *shown) {
+                    correct = true;
+                    break;
+                }
+            }
+        }
+        if skipped {
+            println!();
+            continue;
+        }
+        if let Some(ref mut scoredata) = optscoredata {
+            scoredata.addtimedscore(vocaitem, correct, asked.elapsed().as_millis() as u64);
+        }
+        session.record(vocaitem, correct, optscoredata.as_deref());
+        history.push(before);
+        if correct {
+            println!("{} {}", Green.paint("Correct!"), vocaitem.word);
+        } else {
+            println!("The correct word is: {}", Green.paint(&vocaitem.word));
+        }
+        println!();
+    }
+}
+
+///Returns the letters of the word in random order, with the given number of decoy letters from
+///the pool mixed in. Tries not to return the word itself.
+fn scramble(word: &str, decoys: usize, pool: &[char]) -> Vec<char> {
+    let mut letters: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    for _ in 0..decoys {
+        if !pool.is_empty() {
+            letters.push(pool[thread_rng().gen_range(0, pool.len())]);
+        }
+    }
+    let original = letters.clone();
+    for _ in 0..10 {
+        thread_rng().shuffle(&mut letters);
+        if letters != original {
+            break;
+        }
+    }
+    letters
+}
+
+///Anagram game: unscramble the letters of a word, with the translation as a clue. Decoy letters
+///(taken from other words in the list) make it harder.
+fn anagram(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, decoys: usize, phon: bool, filtertags: Option<&Vec<&str>>, session: &mut Session) {
+    let instructions = "type the word, p for phonetic transcription, q to quit, ENTER to skip; S to suspend, B to bury, K to mark as known, U to undo the last answer";
+    println!("ANAGRAM ({})", instructions);
+    println!("---------------------------------------------------------------------------------");
+    let guesses = 3;
+    let pool: Vec<char> = data.items.iter()
+        .filter(|item| item.filter(filtertags))
+        .flat_map(|item| item.word.to_lowercase().chars().filter(|c| c.is_alphabetic()).collect::<Vec<char>>())
+        .collect();
+    let mut history: Vec<Answer> = Vec::new();
+    let mut redo: Option<&VocaItem> = None;
+    while !session.done() {
+        //select a random item with enough letters to scramble, or the one of the undone answer
+        let vocaitem = match redo.take().or_else(|| data.pickwith(optscoredata.as_deref_mut(), filtertags, false, |item| item.word.chars().filter(|c| c.is_alphabetic()).count() > 1)) {
+            Some(vocaitem) => vocaitem,
+            None => {
+                eprintln!("No items left to practise");
+                return;
+            }
+        };
+        let before = Answer::new(vocaitem, optscoredata.as_deref(), session);
+        if let Some(ref mut scoredata) = optscoredata {
+            scoredata.seen(vocaitem);
+        }
+        let letters: Vec<String> = scramble(&vocaitem.word, decoys, &pool).iter().map(|c| c.to_string()).collect();
+        if decoys > 0 {
+            println!("{}: {}    ({}, with {} decoy letters)", Blue.paint("Unscramble"), letters.join(" "), vocaitem.translation, decoys);
+        } else {
+            println!("{}: {}    ({})", Blue.paint("Unscramble"), letters.join(" "), vocaitem.translation);
+        }
+        if phon {
+            println!("{}", vocaitem.transcription);
+        }
+        let asked = Instant::now();
+        let mut correct = false;
+        let mut skipped = false; //the item was excluded or an earlier answer was undone
+        for _ in 0..guesses {
+            //get response from user
+            if let Some(response) = getinputline() {
+                if response == "p" {
+                    println!("{}", vocaitem.transcription);
+                    continue;
+                } else if response == "q" {
+                    return;
+                } else if response == "h" {
+                    println!("{}",instructions);
+                    continue;
+                } else if response == "U" {
+                    redo = undolast(&mut history, optscoredata.as_deref_mut(), session);
+                    if redo.is_some() {
+                        skipped = true;
+                        break;
+                    }
+                    continue;
+                } else if let Some(message) = optscoredata.as_deref_mut().and_then(|scoredata| excludeitem(&response, vocaitem, scoredata)) {
+                    println!("{}", Blue.paint(message));
+                    skipped = true;
+                    break;
+                } else {
+                    correct = response.trim().to_lowercase() == vocaitem.word.to_lowercase();
+                    if correct {
+                        println!("{}", Green.paint("Correct!"));
+                        break;
+                    } else if stripdiacritics(&response.trim().to_lowercase()) == stripdiacritics(&vocaitem.word.to_lowercase()) {
+                        println!("{} Mind the diacritics. Try again (or ENTER to skip)", Red.paint("Almost!"));
+                        continue;
+                    }
+                }
+            } else {
+                break;
+            }
+            println!("{} Try again (or ENTER to skip)", Red.paint("Incorrect!"));
+        }
+        if skipped {
+            println!();
+            continue;
+        }
+        if let Some(ref mut scoredata) = optscoredata {
+            scoredata.addtimedscore(vocaitem, correct, asked.elapsed().as_millis() as u64);
+        }
+        session.record(vocaitem, correct, optscoredata.as_deref());
+        history.push(before);
+        if !correct {
+            println!("The correct word is: {}", Green.paint(&vocaitem.word));
+        }
+        println!();
+    }
+}
+
+///Speed round: answer as many items as possible within the time limit. A correct answer earns
+///10 points plus a bonus of up to 10 points for answering quickly, and every 5 correct answers in
+///a row raise the multiplier by one.
+fn speedround(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, seconds: u64, fields: &FieldPair, phon: bool, filtertags: Option<&Vec<&str>>, session: &mut Session) {
+    let instructions = "answer as fast as you can, q to quit, ENTER to skip; S to suspend, B to bury, K to mark as known, U to undo the last answer";
+    println!("SPEED ROUND ({} seconds, {})", seconds, instructions);
+    println!("---------------------------------------------------------------------------------");
+    let started = Instant::now();
+    let mut points: u32 = 0;
+    let mut streak: u32 = 0;
+    let mut beststreak: u32 = 0;
+    let mut correctcount: u32 = 0;
+    //the points and streaks before every answer, so answers can be undone
+    let mut history: Vec<(Answer, (u32, u32, u32, u32))> = Vec::new();
+    while !session.done() && started.elapsed().as_secs() < seconds {
+        let previous = (points, streak, beststreak, correctcount);
+        let vocaitem = match data.pickwith(optscoredata.as_deref_mut(), filtertags, false, |item| fields.applies(item)) {
+            Some(vocaitem) => vocaitem,
+            None => {
+                eprintln!("No items left to practise");
+                break;
+            }
+        };
+        print!("[{:>3}s left, {} points] ", seconds.saturating_sub(started.elapsed().as_secs()), points);
+        fieldprompt(vocaitem, fields, phon);
+        let before = Answer::new(vocaitem, optscoredata.as_deref(), session);
+        if let Some(ref mut scoredata) = optscoredata {
+            scoredata.seen(vocaitem);
+        }
+        let asked = Instant::now();
+        let response = getinputline();
+        let millis = asked.elapsed().as_millis() as u64;
+        if started.elapsed().as_secs() >= seconds {
+            println!("{}", Red.paint("Too late!"));
+            break;
+        }
+        let correct = match response {
+            Some(ref response) if response == "q" => break,
+            Some(ref response) if response == "U" => {
+                match history.pop() {
+                    Some((answer, previous)) => {
+                        let vocaitem = answer.undo(optscoredata.as_deref_mut(), session);
+                        (points, streak, beststreak, correctcount) = previous;
+                        println!("{}", Blue.paint(format!("Undid the answer for {}", vocaitem.word)));
+                    },
+                    None => println!("{}", Red.paint("Nothing to undo"))
+                }
+                continue;
+            },
+            Some(ref response) => match optscoredata.as_deref_mut().and_then(|scoredata| excludeitem(response, vocaitem, scoredata)) {
+                Some(message) => {
+                    println!("{}", Blue.paint(message));
+                    continue;
+                },
+                None => checktranslation(response, &fields.answer(vocaitem))
+            },
+            None => false
+        };
+        if correct {
+            streak += 1;
+            beststreak = beststreak.max(streak);
+            correctcount += 1;
+            let speedbonus = 10u64.saturating_sub(millis / 500) as u32;
+            let multiplier = 1 + streak / 5;
+            let earned = (10 + speedbonus) * multiplier;
+            points += earned;
+            println!("{} +{} ({:.1}s{})", Green.paint("Correct!"), earned, millis as f64 / 1000.0, if multiplier > 1 { format!(", streak of {}: x{}", streak, multiplier) } else { String::new() });
+        } else {
+            streak = 0;
+            println!("{} The correct {} is: {}", Red.paint("Incorrect!"), fields.answer, Green.paint(fields.answer(vocaitem)));
+        }
+        if let Some(ref mut scoredata) = optscoredata {
+            scoredata.addtimedscore(vocaitem, correct, millis);
+        }
+        session.record(vocaitem, correct, optscoredata.as_deref());
+        history.push((before, previous));
+    }
+    println!();
+    println!("TIME'S UP! {} points ({} correct, longest streak {})", points, correctcount, beststreak);
     if let Some(ref mut scoredata) = optscoredata {
-        vocaitem = data.pick(Some(scoredata),filtertags,true);
+        //only rounds of the same length are comparable
+        let game = format!("speed{}", seconds);
+        let previous = scoredata.personalbest.get(&game).copied().unwrap_or(0);
+        if scoredata.addpersonalbest(&game, points) {
+            println!("{}", Green.paint("New personal best!"));
+        } else {
+            println!("Personal best: {} points", previous);
+        }
+    }
+    println!();
+}
+
+///Shows the full card for an item, used to introduce new items
+fn showcard(vocaitem: &VocaItem, template: Option<&CardTemplate>) {
+    if let Some(template) = template {
+        println!("{}", template.front(vocaitem));
+        println!("{}", template.back(vocaitem));
+        return;
+    }
+    println!("{}", Blue.paint(&vocaitem.word));
+    for (label, value) in [("Transcription", &vocaitem.transcription), ("Translation", &vocaitem.translation), ("Comment", &vocaitem.comment)].iter() {
+        if !value.is_empty() {
+            println!("  {:<14} {}", format!("{}:", label), value);
+        }
+    }
+    if !vocaitem.example.is_empty() {
+        println!("  {:<14} {}", "Example:", vocaitem.plainexample());
+    }
+    for (key, value) in vocaitem.extra.iter() {
+        println!("  {:<14} {}", format!("{}:", key), value);
+    }
+}
+
+///Learning mode: introduces items that were never seen before by showing the full card, drills
+///them in short cycles until each was answered correctly twice in a row, and then continues with
+///a regular quiz in which they are mixed with the rest
+#[allow(clippy::too_many_arguments)]
+fn learn(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, newcount: usize, dailylimit: usize, fields: &FieldPair, template: Option<&CardTemplate>, phon: bool, filtertags: Option<&Vec<&str>>, session: &mut Session) {
+    let instructions = "type the answer, p for phonetic transcription, q to quit, ENTER to skip; S to suspend, B to bury, K to mark as known, U to undo the last answer";
+    let introducedtoday = optscoredata.as_deref().map(|scoredata| scoredata.introducedtoday()).unwrap_or(0);
+    let newcount = std::cmp::min(newcount, dailylimit.saturating_sub(introducedtoday));
+    let newitems: Vec<&VocaItem> = data.available(optscoredata.as_deref(), filtertags, |item| fields.applies(item)).into_iter()
+        .filter(|item| optscoredata.as_deref().map(|scoredata| !scoredata.lastseen.contains_key(&item.id_as_string())).unwrap_or(true))
+        .take(newcount)
+        .collect();
+    if newitems.is_empty() {
+        if introducedtoday >= dailylimit {
+            println!("Daily limit of {} new items reached, continuing with review", dailylimit);
+        } else {
+            println!("No new items to introduce, continuing with review");
+        }
+        println!();
+    } else {
+        println!("LEARN ({} new items; read each card and press ENTER, q to quit)", newitems.len());
+        println!("---------------------------------------------------------------------------------");
+        for (i, vocaitem) in newitems.iter().enumerate() {
+            print!("{}/{} ", i + 1, newitems.len());
+            showcard(vocaitem, template);
+            if let Some(ref mut scoredata) = optscoredata {
+                scoredata.seen(vocaitem);
+                scoredata.introduce(vocaitem);
+            }
+            if getinputline().as_deref() == Some("q") {
+                return;
+            }
+            println!();
+        }
+        println!("DRILL ({})", instructions);
+        println!("---------------------------------------------------------------------------------");
+        //number of consecutive correct answers per new item, an item is learned after two
+        let mut streaks: Vec<u32> = vec![0; newitems.len()];
+        let mut cycle = 1;
+        let mut history: Vec<(Answer, usize, u32)> = Vec::new();
+        while streaks.iter().any(|streak| *streak < 2) {
+            let mut order: Vec<usize> = (0..newitems.len()).filter(|i| streaks[*i] < 2).collect();
+            thread_rng().shuffle(&mut order);
+            println!("{}", Blue.paint(format!("Cycle {} ({} items left)", cycle, order.len())));
+            for i in order {
+                let vocaitem = newitems[i];
+                fieldprompt(vocaitem, fields, phon);
+                let before = Answer::new(vocaitem, optscoredata.as_deref(), session);
+                let asked = Instant::now();
+                let correct = loop {
+                    match getinputline() {
+                        Some(ref response) if response == "q" => return,
+                        Some(ref response) if response == "p" => println!("{}", vocaitem.transcription),
+                        Some(ref response) if response == "h" => println!("{}", instructions),
+                        Some(ref response) if response == "U" => {
+                            match history.pop() {
+                                Some((answer, j, streak)) => {
+                                    let undone = answer.undo(optscoredata.as_deref_mut(), session);
+                                    streaks[j] = streak;
+                                    println!("{}", Blue.paint(format!("Undid the answer for {}, it will be asked again", undone.word)));
+                                },
+                                None => println!("{}", Red.paint("Nothing to undo"))
+                            }
+                        },
+                        Some(response) => match optscoredata.as_deref_mut().and_then(|scoredata| excludeitem(&response, vocaitem, scoredata)) {
+                            Some(message) => {
+                                println!("{}", Blue.paint(message));
+                                break None;
+                            },
+                            None => break Some(checktranslation(&response, &fields.answer(vocaitem)))
+                        },
+                        None => break Some(false)
+                    }
+                };
+                let correct = match correct {
+                    Some(correct) => correct,
+                    None => {
+                        //an excluded item is not drilled any further
+                        streaks[i] = 2;
+                        println!();
+                        continue;
+                    }
+                };
+                history.push((before, i, streaks[i]));
+                if correct {
+                    streaks[i] += 1;
+                    println!("{}", Green.paint("Correct!"));
+                } else {
+                    streaks[i] = 0;
+                    println!("{} The correct {} is: {}", Red.paint("Incorrect!"), fields.answer, Green.paint(fields.answer(vocaitem)));
+                }
+                if let Some(ref mut scoredata) = optscoredata {
+                    scoredata.addtimedscore(vocaitem, correct, asked.elapsed().as_millis() as u64);
+                }
+                session.record(vocaitem, correct, optscoredata.as_deref());
+                if session.done() {
+                    return;
+                }
+                println!();
+            }
+            cycle += 1;
+        }
+        println!("{}", Green.paint("All new items learned, continuing with review"));
+        println!();
+    }
+    quiz(data, optscoredata, fields, phon, filtertags, session);
+}
+
+///Restores a suspended, buried or known item, or lists all such items if no word is given
+fn restore(data: &VocaList, scoredata: &mut VocaScore, word: Option<&str>) -> bool {
+    if let Some(word) = word {
+        match data.items.iter().find(|item| item.word == word) {
+            Some(vocaitem) if scoredata.restore(vocaitem) => {
+                println!("{} is back in practice", vocaitem.word);
+                true
+            },
+            Some(vocaitem) => {
+                eprintln!("{} was not suspended, buried or known", vocaitem.word);
+                false
+            },
+            None => {
+                eprintln!("Not found");
+                false
+            }
+        }
+    } else {
+        for vocaitem in data.items.iter() {
+            let id = vocaitem.id_as_string();
+            if scoredata.suspended.contains_key(&id) {
+                println!("{}\tsuspended", vocaitem.word);
+            } else if scoredata.known.contains_key(&id) {
+                println!("{}\tknown", vocaitem.word);
+            } else if scoredata.excluded(vocaitem) {
+                println!("{}\tburied", vocaitem.word);
+            }
+        }
+        true
+    }
+}
+
+///Picks and prints a random item, provides no further interaction. If a card template is given,
+///the front of the card is printed, and the back as well if translations are requested.
+fn pick(data: &VocaList, optscoredata: Option<&mut VocaScore>, template: Option<&CardTemplate>, phon: bool, translation: bool, example: bool, filtertags: Option<&Vec<&str>>) {
+    //select a random item
+    let vocaitem = match data.pick(optscoredata, filtertags, true) {
+        Some(vocaitem) => vocaitem,
+        None => {
+            eprintln!("No items to pick from");
+            return;
+        }
+    };
+    if let Some(template) = template {
+        println!("{}", template.front(vocaitem));
+        if translation {
+            println!("{}", template.back(vocaitem));
+        }
     } else {
-        vocaitem = data.pick(None,filtertags,true);
+        vocaitem.print(phon, translation, example);
     }
-    vocaitem.print(phon, translation, example);
 }
 
 ///Looks up and prints a specific item, provides no further interaction
@@ -130,21 +824,30 @@ fn find(data: &VocaList, word: &str, mut optscoredata: Option<&mut VocaScore>, p
 
 
 ///Quiz
-fn quiz(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, phon: bool, filtertags: Option<&Vec<&str>>) {
-    let instructions = "type p for phonetic transcription, x for example, q to quit, ENTER to skip";
+fn quiz(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, fields: &FieldPair, phon: bool, filtertags: Option<&Vec<&str>>, session: &mut Session) {
+    let instructions = "type p for phonetic transcription, x for example, q to quit, ENTER to skip; S to suspend, B to bury, K to mark as known, U to undo the last answer";
     println!("QUIZ ({})", instructions);
     println!("---------------------------------------------------------------------------------");
     let guesses = 3;
-    loop {
-        //select a random item
-        let vocaitem;
+    let mut history: Vec<Answer> = Vec::new();
+    let mut redo: Option<&VocaItem> = None;
+    while !session.done() {
+        //select a random item, or the one of the undone answer
+        let vocaitem = match redo.take().or_else(|| data.pickwith(optscoredata.as_deref_mut(), filtertags, false, |item| fields.applies(item))) {
+            Some(vocaitem) => vocaitem,
+            None => {
+                eprintln!("No items left to practise");
+                return;
+            }
+        };
+        let before = Answer::new(vocaitem, optscoredata.as_deref(), session);
         if let Some(ref mut scoredata) = optscoredata {
-            vocaitem = data.pick(Some(scoredata),filtertags,true);
-        } else {
-            vocaitem = data.pick(None,filtertags,true);
+            scoredata.seen(vocaitem);
         }
-        quizprompt(vocaitem, phon);
+        fieldprompt(vocaitem, fields, phon);
+        let asked = Instant::now();
         let mut correct = false;
+        let mut skipped = false; //the item was excluded or an earlier answer was undone
         for _ in 0..guesses {
             //get response from user
             if let Some(response) = getinputline() {
@@ -152,18 +855,32 @@ fn quiz(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, phon: bool, f
                     println!("{}", vocaitem.transcription);
                     continue;
                 } else if response == "x" {
-                    println!("{}", vocaitem.example);
+                    println!("{}", vocaitem.plainexample());
                     continue;
                 } else if response == "q" {
                     return;
                 } else if response == "h" {
                     println!("{}",instructions);
                     continue;
+                } else if response == "U" {
+                    redo = undolast(&mut history, optscoredata.as_deref_mut(), session);
+                    if redo.is_some() {
+                        skipped = true;
+                        break;
+                    }
+                    continue;
+                } else if let Some(message) = optscoredata.as_deref_mut().and_then(|scoredata| excludeitem(&response, vocaitem, scoredata)) {
+                    println!("{}", Blue.paint(message));
+                    skipped = true;
+                    break;
                 } else {
-                    correct = checktranslation(&response, &vocaitem.translation);
+                    correct = checktranslation(&response, &fields.answer(vocaitem));
                     if correct {
                         println!("{}", Green.paint("Correct!"));
                         break;
+                    } else if let (Some(ref mut scoredata), Some(confuseditem)) = (optscoredata.as_mut(), data.findbyfield(&fields.answer, &response, None)) {
+                        //the answer belongs to another item
+                        scoredata.addconfusion(vocaitem, confuseditem);
                     }
                 }
             } else {
@@ -171,199 +888,1449 @@ fn quiz(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, phon: bool, f
             }
             println!("{} Try again (or ENTER to skip)", Red.paint("Incorrect!"));
         }
+        if skipped {
+            println!();
+            continue;
+        }
         if let Some(ref mut scoredata) = optscoredata {
-            scoredata.addscore(&vocaitem, correct);
+            scoredata.addtimedscore(vocaitem, correct, asked.elapsed().as_millis() as u64);
         }
+        session.record(vocaitem, correct, optscoredata.as_deref());
+        history.push(before);
         if !correct {
-            println!("The correct translation is: {}", Green.paint(&vocaitem.translation));
+            println!("The correct {} is: {}", fields.answer, Green.paint(fields.answer(vocaitem)));
         }
         println!();
     }
 }
 
-fn getquizoptions<'a>(data: &'a VocaList, correctitem: &'a VocaItem, optioncount: u32, filtertags: Option<&Vec<&str>>) -> (Vec<&'a VocaItem>, u32) {
-    //reserve an index for the correct option
-    let correctindex: f64 = rand::random::<f64>() * (optioncount as f64);
-    let correctindex: u32 = correctindex as u32;
-    let mut options: Vec<&VocaItem> = Vec::new();
-    for i in 0..optioncount {
-        if i == correctindex {
-            options.push(correctitem);
-        } else {
-            loop {
-                let candidate  = data.pick(None, filtertags, false);
-                if candidate.id() != correctitem.id() {
-                    options.push(candidate);
-                    break;
-                }
-            }
-        }
+///Cloze quiz: fill in the blank in the example sentence
+fn clozequiz(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, phon: bool, filtertags: Option<&Vec<&str>>, session: &mut Session) {
+    if data.available(optscoredata.as_deref(), filtertags, |item| item.cloze().is_some()).is_empty() {
+        eprintln!("No items with usable examples");
+        return;
     }
-    (options, correctindex)
-}
-
-///Multiple-choice Quiz
-fn multiquiz(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, choicecount: u32, phon: bool, filtertags: Option<&Vec<&str>>) {
-    let instructions = "type p for phonetic transcription, x for example, q to quit, ENTER to skip";
-    println!("MULTIPLE-CHOICE QUIZ ({})",instructions);
-    println!("-------------------------------------------------------------------------------------------------");
-    loop {
-        //select a random item
-        let vocaitem;
+    let instructions = "type the missing word, p for phonetic transcription, q to quit, ENTER to skip; S to suspend, B to bury, K to mark as known, U to undo the last answer";
+    println!("CLOZE QUIZ ({})", instructions);
+    println!("---------------------------------------------------------------------------------");
+    let guesses = 3;
+    let mut history: Vec<Answer> = Vec::new();
+    let mut redo: Option<&VocaItem> = None;
+    while !session.done() {
+        //select a random item that has a usable example, or the one of the undone answer
+        let vocaitem = match redo.take().or_else(|| data.pickwith(optscoredata.as_deref_mut(), filtertags, false, |item| item.cloze().is_some())) {
+            Some(vocaitem) => vocaitem,
+            None => {
+                eprintln!("No items left to practise");
+                return;
+            }
+        };
+        let (question, answer) = vocaitem.cloze().expect("item must have a usable example");
+        let before = Answer::new(vocaitem, optscoredata.as_deref(), session);
         if let Some(ref mut scoredata) = optscoredata {
-            vocaitem = data.pick(Some(scoredata), filtertags, true);
-        } else {
-            vocaitem = data.pick(None, filtertags, true);
-        }
-        quizprompt(vocaitem, phon);
-        let (options, correctindex) = getquizoptions(&data, &vocaitem, choicecount, filtertags);
-        for (i, option) in options.iter().enumerate() {
-            println!("{} - {}", i+1, option.translation);
+            scoredata.seen(vocaitem);
         }
+        println!("{}: {} ({})", Blue.paint("Fill in"), question, vocaitem.translation);
+        let asked = Instant::now();
         let mut correct = false;
-        loop {
+        let mut skipped = false; //the item was excluded or an earlier answer was undone
+        for _ in 0..guesses {
             //get response from user
             if let Some(response) = getinputline() {
                 if response == "p" {
                     println!("{}", vocaitem.transcription);
                     continue;
-                } else if response == "x" {
-                    println!("{}", vocaitem.example);
-                    continue;
                 } else if response == "q" {
                     return;
                 } else if response == "h" {
                     println!("{}",instructions);
                     continue;
-                } else if let Ok(responseindex) = response.parse::<usize>() {
-                    correct = responseindex -1 == correctindex as usize;
+                } else if response == "U" {
+                    redo = undolast(&mut history, optscoredata.as_deref_mut(), session);
+                    if redo.is_some() {
+                        skipped = true;
+                        break;
+                    }
+                    continue;
+                } else if let Some(message) = optscoredata.as_deref_mut().and_then(|scoredata| excludeitem(&response, vocaitem, scoredata)) {
+                    println!("{}", Blue.paint(message));
+                    skipped = true;
                     break;
                 } else {
-                    println!("Enter a number!");
+                    correct = response.trim().to_lowercase() == answer.to_lowercase();
+                    if correct {
+                        println!("{}", Green.paint("Correct!"));
+                        break;
+                    } else if response.trim().to_lowercase() == vocaitem.word.to_lowercase() {
+                        println!("{} That is the right word, but it takes another form here. Try again (or ENTER to skip)", Red.paint("Almost!"));
+                        continue;
+                    }
                 }
             } else {
                 break;
             }
-        }
-        match correct {
-            true => println!("{}", Green.paint("Correct!")),
-            false => println!("{}; the correct translation is: {}", Red.paint("Incorrect"), Green.paint(&vocaitem.translation))
-        }
-        if let Some(ref mut scoredata) = optscoredata {
-            scoredata.addscore(&vocaitem, correct);
+            println!("{} Try again (or ENTER to skip)", Red.paint("Incorrect!"));
+        }
+        if skipped {
+            println!();
+            continue;
+        }
+        if let Some(ref mut scoredata) = optscoredata {
+            scoredata.addtimedscore(vocaitem, correct, asked.elapsed().as_millis() as u64);
+        }
+        session.record(vocaitem, correct, optscoredata.as_deref());
+        history.push(before);
+        if !correct {
+            println!("The correct answer is: {}", Green.paint(&answer));
+        }
+        if phon || !correct {
+            println!("{}", vocaitem.plainexample());
+        }
+        println!();
+    }
+}
+
+fn getquizoptions<'a>(data: &'a VocaList, correctitem: &'a VocaItem, optioncount: u32, filtertags: Option<&Vec<&str>>, fields: &FieldPair, strategy: DistractorStrategy, optscoredata: Option<&VocaScore>) -> (Vec<&'a VocaItem>, u32) {
+    let mut options: Vec<&VocaItem> = data.distractors(correctitem, optioncount as usize - 1, filtertags, fields, strategy, optscoredata);
+    thread_rng().shuffle(&mut options);
+    //insert the correct option at a random index
+    let correctindex: f64 = rand::random::<f64>() * ((options.len() + 1) as f64);
+    let correctindex: u32 = correctindex as u32;
+    options.insert(correctindex as usize, correctitem);
+    (options, correctindex)
+}
+
+///Multiple-choice Quiz
+#[allow(clippy::too_many_arguments)]
+fn multiquiz(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, choicecount: u32, fields: &FieldPair, strategy: DistractorStrategy, phon: bool, filtertags: Option<&Vec<&str>>, session: &mut Session) {
+    let instructions = "type p for phonetic transcription, x for example, q to quit, ENTER to skip; S to suspend, B to bury, K to mark as known, U to undo the last answer";
+    println!("MULTIPLE-CHOICE QUIZ ({})",instructions);
+    println!("-------------------------------------------------------------------------------------------------");
+    let mut history: Vec<Answer> = Vec::new();
+    let mut redo: Option<&VocaItem> = None;
+    while !session.done() {
+        //select a random item, or the one of the undone answer
+        let vocaitem = match redo.take().or_else(|| data.pickwith(optscoredata.as_deref_mut(), filtertags, false, |item| fields.applies(item))) {
+            Some(vocaitem) => vocaitem,
+            None => {
+                eprintln!("No items left to practise");
+                return;
+            }
+        };
+        let before = Answer::new(vocaitem, optscoredata.as_deref(), session);
+        if let Some(ref mut scoredata) = optscoredata {
+            scoredata.seen(vocaitem);
+        }
+        fieldprompt(vocaitem, fields, phon);
+        let (options, _) = getquizoptions(data, vocaitem, choicecount, filtertags, fields, strategy, optscoredata.as_deref());
+        for (i, option) in options.iter().enumerate() {
+            println!("{} - {}", i+1, fields.answer(option));
+        }
+        let asked = Instant::now();
+        let mut correct = false;
+        let mut skipped = false; //the item was excluded or an earlier answer was undone
+        //get response from user, options can be chosen with a single key if there are few enough
+        while let Some(response) = if choicecount <= 9 { getinputkey() } else { getinputline() } {
+            if response == "p" {
+                println!("{}", vocaitem.transcription);
+                continue;
+            } else if response == "x" {
+                println!("{}", vocaitem.plainexample());
+                continue;
+            } else if response == "q" {
+                return;
+            } else if response == "h" {
+                println!("{}",instructions);
+                continue;
+            } else if response == "U" {
+                redo = undolast(&mut history, optscoredata.as_deref_mut(), session);
+                if redo.is_some() {
+                    skipped = true;
+                    break;
+                }
+                continue;
+            } else if let Some(message) = optscoredata.as_deref_mut().and_then(|scoredata| excludeitem(&response, vocaitem, scoredata)) {
+                println!("{}", Blue.paint(message));
+                skipped = true;
+                break;
+            } else if let Some(option) = response.parse::<usize>().ok().and_then(|responseindex| options.get(responseindex.wrapping_sub(1))) {
+                correct = option.id() == vocaitem.id();
+                if let (false, Some(ref mut scoredata)) = (correct, optscoredata.as_mut()) {
+                    scoredata.addconfusion(vocaitem, option);
+                }
+                break;
+            } else {
+                println!("Enter a number!");
+            }
+        }
+        if skipped {
+            println!();
+            continue;
+        }
+        match correct {
+            true => println!("{}", Green.paint("Correct!")),
+            false => println!("{}; the correct {} is: {}", Red.paint("Incorrect"), fields.answer, Green.paint(fields.answer(vocaitem)))
+        }
+        if let Some(ref mut scoredata) = optscoredata {
+            scoredata.addtimedscore(vocaitem, correct, asked.elapsed().as_millis() as u64);
+        }
+        session.record(vocaitem, correct, optscoredata.as_deref());
+        history.push(before);
+        println!();
+    }
+}
+
+///Resolve the confusable pairs in the score data to items
+fn getconfusablepairs<'a>(data: &'a VocaList, scoredata: &VocaScore) -> Vec<(&'a VocaItem, &'a VocaItem, u32)> {
+    scoredata.confusablepairs().iter().filter_map(|(id, otherid, count)| {
+        match (data.findbyid(id), data.findbyid(otherid)) {
+            (Some(item), Some(other)) => Some((item, other, *count)),
+            _ => None
+        }
+    }).collect()
+}
+
+///Prints the pairs of items that were confused most often
+fn confusionreport(data: &VocaList, scoredata: &VocaScore) {
+    for (item, other, count) in getconfusablepairs(data, scoredata) {
+        println!("{}\t{}\t{}\t{}\t{}", count, item.word, item.translation, other.word, other.translation);
+    }
+}
+
+///Confusion drill: presents items that were confused with eachother before, the learner has to
+///pick the right translation out of the pair
+fn confusiondrill(data: &VocaList, scoredata: &mut VocaScore, phon: bool, session: &mut Session) {
+    let mut pairs: Vec<(&VocaItem, &VocaItem, u32)> = getconfusablepairs(data, scoredata).into_iter().filter(|(item, other, _)| !scoredata.excluded(item) && !scoredata.excluded(other)).collect();
+    if pairs.is_empty() {
+        println!("No confused items yet, play some quizzes first!");
+        return;
+    }
+    let mut total: u32 = pairs.iter().map(|(_, _, count)| count).sum();
+    let instructions = "type 1 or 2 to choose, p for phonetic transcription, x for example, q to quit; S to suspend, B to bury, K to mark as known, U to undo the last answer";
+    println!("CONFUSION DRILL ({})", instructions);
+    println!("---------------------------------------------------------------------------------");
+    let mut history: Vec<(Answer, &VocaItem)> = Vec::new();
+    let mut redo: Option<(&VocaItem, &VocaItem)> = None;
+    while !session.done() {
+        //select a pair, pairs that were confused more often are more likely
+        let choice: f64 = rand::random::<f64>() * total as f64;
+        let mut cumulative: f64 = 0.0;
+        let mut pair = pairs[0];
+        for candidate in pairs.iter() {
+            cumulative += candidate.2 as f64;
+            if cumulative >= choice {
+                pair = *candidate;
+                break;
+            }
+        }
+        let (vocaitem, other) = match redo.take() {
+            Some(undone) => undone,
+            None if rand::random::<bool>() => (pair.0, pair.1),
+            None => (pair.1, pair.0)
+        };
+        let before = Answer::new(vocaitem, Some(scoredata), session);
+        scoredata.seen(vocaitem);
+        quizprompt(vocaitem, phon);
+        let mut options = [vocaitem, other];
+        thread_rng().shuffle(&mut options);
+        for (i, option) in options.iter().enumerate() {
+            println!("{} - {}", i+1, option.translation);
+        }
+        let asked = Instant::now();
+        let mut correct = false;
+        let mut skipped = false; //the item was excluded or an earlier answer was undone
+        while let Some(response) = getinputkey() {
+            if response == "p" {
+                println!("{}", vocaitem.transcription);
+            } else if response == "x" {
+                println!("{}", vocaitem.plainexample());
+            } else if response == "q" {
+                return;
+            } else if response == "h" {
+                println!("{}",instructions);
+            } else if response == "U" {
+                match history.pop() {
+                    Some((answer, other)) => {
+                        let vocaitem = answer.undo(Some(scoredata), session);
+                        println!("{}", Blue.paint(format!("Undid the answer for {}, try again", vocaitem.word)));
+                        redo = Some((vocaitem, other));
+                        skipped = true;
+                        break;
+                    },
+                    None => println!("{}", Red.paint("Nothing to undo"))
+                }
+            } else if response == "1" || response == "2" {
+                correct = options[if response == "1" { 0 } else { 1 }].id() == vocaitem.id();
+                break;
+            } else if let Some(message) = excludeitem(&response, vocaitem, scoredata) {
+                println!("{}", Blue.paint(message));
+                //the pairs with the excluded item are not drilled any further
+                pairs.retain(|(item, other, _)| !scoredata.excluded(item) && !scoredata.excluded(other));
+                total = pairs.iter().map(|(_, _, count)| count).sum();
+                skipped = true;
+                break;
+            } else {
+                println!("Enter 1 or 2!");
+            }
+        }
+        if pairs.is_empty() {
+            println!("No confused items left");
+            return;
+        }
+        if skipped {
+            println!();
+            continue;
+        }
+        match correct {
+            true => println!("{}", Green.paint("Correct!")),
+            false => {
+                println!("{}; the correct translation is: {}", Red.paint("Incorrect"), Green.paint(&vocaitem.translation));
+                scoredata.addconfusion(vocaitem, other);
+            }
+        }
+        scoredata.addtimedscore(vocaitem, correct, asked.elapsed().as_millis() as u64);
+        session.record(vocaitem, correct, Some(scoredata));
+        history.push((before, other));
+        println!();
+    }
+}
+
+///Returns the label for a translation in the match quiz: a-z, then aa, ab, etc..
+fn matchlabel(index: usize) -> String {
+    let mut label: Vec<char> = Vec::new();
+    let mut index = index + 1;
+    while index > 0 {
+        index -= 1;
+        label.insert(0, (b'a' + (index % 26) as u8) as char);
+        index /= 26;
+    }
+    label.into_iter().collect()
+}
+
+///Parses a match quiz label (as produced by matchlabel()) back to an index
+fn parsematchlabel(label: &str) -> Option<usize> {
+    let mut index: usize = 0;
+    for c in label.chars() {
+        if !c.is_ascii_lowercase() {
+            return None;
+        }
+        index = index.checked_mul(26)?.checked_add((c as u8 - b'a') as usize + 1)?;
+    }
+    index.checked_sub(1)
+}
+
+///Parses a match quiz response into pairs of (item index, translation index). A response may
+///contain multiple pairs, each consisting of a number and a letter label in either order, with or
+///without separators in between (e.g: 1a, 1 a, a1, 1a 2b, 10c,11d)
+fn parsematchresponse(response: &str) -> Result<Vec<(usize,usize)>, String> {
+    //split into runs of digits and runs of letters, anything else separates
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for c in response.to_lowercase().chars() {
+        let sameclass = current.chars().last().map(|last| last.is_ascii_digit() == c.is_ascii_digit()).unwrap_or(true);
+        if (!c.is_ascii_alphanumeric() || !sameclass) && !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+        if c.is_ascii_alphanumeric() {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    if tokens.is_empty() || !tokens.len().is_multiple_of(2) {
+        return Err("Expected pairs of a number and a letter (for example: 1a)".to_string());
+    }
+    let mut pairs: Vec<(usize,usize)> = Vec::new();
+    for pair in tokens.chunks(2) {
+        let (number, label) = if pair[0].chars().all(|c| c.is_ascii_digit()) {
+            (&pair[0], &pair[1])
+        } else {
+            (&pair[1], &pair[0])
+        };
+        match (number.parse::<usize>().ok().and_then(|number| number.checked_sub(1)), parsematchlabel(label)) {
+            (Some(number), Some(label)) => pairs.push((number, label)),
+            _ => return Err(format!("Invalid match: {}{} (expected a number and a letter, for example: 1a)", pair[0], pair[1]))
+        }
+    }
+    Ok(pairs)
+}
+
+///Processes a single match in the match quiz, returns false if the match was invalid. The time
+///it took to make the match is given in milliseconds.
+#[allow(clippy::too_many_arguments)]
+fn processmatch(vocaitems: &[&VocaItem], mappings: &[usize], fields: &FieldPair, (first, second): (usize, usize), millis: u64, optscoredata: &mut Option<&mut VocaScore>, solved: &mut Vec<usize>, session: &mut Session) -> bool {
+    let (vocaitem, mapped) = match (vocaitems.get(first), mappings.get(second)) {
+        (Some(vocaitem), Some(mapped)) => (vocaitem, *mapped),
+        _ => {
+            println!("{}", Red.paint(format!("Invalid input: {}{}", first + 1, matchlabel(second))));
+            return false;
+        }
+    };
+    if solved.contains(&first) {
+        println!("{}", Red.paint(format!("{} was already solved!", first + 1)));
+        return false;
+    } else if solved.contains(&mapped) && mapped != first {
+        println!("{}", Red.paint(format!("{} was already used!", matchlabel(second))));
+        return false;
+    }
+    //identical answers are interchangeable
+    let correct: bool = mapped == first || fields.answer(vocaitems[mapped]) == fields.answer(vocaitem);
+    if correct {
+        solved.push(first);
+        println!("{}{}: {}", first + 1, matchlabel(second), Green.paint("Correct!"));
+    } else {
+        println!("{}{}: {}", first + 1, matchlabel(second), Red.paint("Wrong!"));
+    }
+    if let Some(ref mut scoredata) = optscoredata {
+        scoredata.addtimedscore(vocaitem, correct, millis);
+        if !correct {
+            scoredata.addconfusion(vocaitem, vocaitems[mapped]);
+        }
+    }
+    session.record(vocaitem, correct, optscoredata.as_deref());
+    true
+}
+
+///Match quiz, if a round is interrupted its state is returned so it can be resumed later
+#[allow(clippy::too_many_arguments)]
+fn matchquiz(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, matchcount: usize, fields: &FieldPair, phon: bool, filtertags: Option<&Vec<&str>>, session: &mut Session, mut resume: Option<&SavedGame>) -> Option<SavedGame> {
+    println!("MATCH QUIZ (Enter a match by entering a number and a letter, multiple matches may be entered at once, S, B or K and a number (e.g. S 2) to suspend, bury or mark an item as known, U to undo the last match, enter q to quit (resume later with vj resume), ENTER to skip)");
+    println!("----------------------------------------------------------------------------------------");
+    //we can't draw more distinct items than there are
+    let available: HashSet<String> = data.available(optscoredata.as_deref(), filtertags, |item| fields.applies(item)).iter().map(|item| item.id_as_string()).collect();
+    let matchcount = std::cmp::min(matchcount, available.len());
+    if matchcount == 0 {
+        eprintln!("No items to match");
+        return None;
+    }
+    while !session.done() {
+        //continue the interrupted round, unless its items are no longer there
+        let savedround: Option<(Vec<&VocaItem>, Vec<usize>, Vec<usize>)> = resume.take().and_then(|savedgame| {
+            let vocaitems: Option<Vec<&VocaItem>> = savedgame.items.iter().map(|id| data.findbyid(id)).collect();
+            vocaitems.filter(|vocaitems| vocaitems.len() == savedgame.mappings.len()).map(|vocaitems| (vocaitems, savedgame.mappings.clone(), savedgame.solved.clone()))
+        });
+        let (vocaitems, mappings, mut solved) = match savedround {
+            Some(savedround) => savedround,
+            None => {
+                let vocaitems = data.pickdistinct(optscoredata.as_deref_mut(), filtertags, matchcount, true, |item| fields.applies(item));
+                //create a random order for presentation of the translations
+                //values correspond to indices in vocaitems
+                let mut mappings: Vec<usize> = (0..vocaitems.len()).collect();
+                thread_rng().shuffle(&mut mappings);
+                (vocaitems, mappings, Vec::new())
+            }
+        };
+
+        //the solved items before every match, so matches can be undone
+        let mut history: Vec<(Answer, Vec<usize>)> = Vec::new();
+        //excluded items are taken out of the round, an undo does not bring them back
+        let mut excluded: Vec<usize> = Vec::new();
+        loop {
+            for (i, vocaitem) in vocaitems.iter().enumerate() {
+                if !solved.contains(&i) {
+                    if phon && fields.prompt != "transcription" && fields.answer != "transcription" {
+                        println!("{}) {} ({})", i+1, fields.prompt(vocaitem), vocaitem.transcription);
+                    } else {
+                        println!("{}) {}", i+1, fields.prompt(vocaitem));
+                    }
+                }
+            }
+            println!("{}", Blue.paint("---match with:---"));
+            for (i, mappedindex) in mappings.iter().enumerate() {
+                if !solved.contains(mappedindex) {
+                    println!("{}) {}", matchlabel(i), fields.answer(vocaitems[*mappedindex]));
+                }
+            }
+            //get response from user
+            let asked = Instant::now();
+            if let Some(response) = getmatchinput(vocaitems.len()) {
+                if response == "U" {
+                    match history.pop() {
+                        Some((answer, previous)) => {
+                            let vocaitem = answer.undo(optscoredata.as_deref_mut(), session);
+                            solved = previous;
+                            for index in excluded.iter() {
+                                if !solved.contains(index) {
+                                    solved.push(*index);
+                                }
+                            }
+                            println!("{}", Blue.paint(format!("Undid the match for {}", fields.prompt(vocaitem))));
+                        },
+                        None => println!("{}", Red.paint("Nothing to undo"))
+                    }
+                    continue;
+                } else if let Some((command, target)) = parseexclusion(&response) {
+                    match target.parse::<usize>().ok().and_then(|number| number.checked_sub(1)).filter(|index| *index < vocaitems.len() && !solved.contains(index)) {
+                        Some(index) => {
+                            if let Some(message) = optscoredata.as_deref_mut().and_then(|scoredata| excludeitem(command, vocaitems[index], scoredata)) {
+                                println!("{}", Blue.paint(format!("{}: {}", fields.prompt(vocaitems[index]), message)));
+                                solved.push(index);
+                                excluded.push(index);
+                            }
+                        },
+                        None => println!("{}", Red.paint(format!("No such item: {}", target)))
+                    }
+                    if solved.len() == vocaitems.len() {
+                        break;
+                    }
+                    continue;
+                } else if response == "q" {
+                    return Some(SavedGame {
+                        items: vocaitems.iter().map(|vocaitem| vocaitem.id_as_string()).collect(),
+                        mappings,
+                        solved,
+                        ..Default::default()
+                    });
+                }
+                match parsematchresponse(&response) {
+                    Ok(pairs) => {
+                        //if several matches were entered at once, they share the time it took
+                        let millis = asked.elapsed().as_millis() as u64 / pairs.len() as u64;
+                        for pair in pairs {
+                            let before = vocaitems.get(pair.0).map(|vocaitem| (Answer::new(vocaitem, optscoredata.as_deref(), session), solved.clone()));
+                            if processmatch(&vocaitems, &mappings, fields, pair, millis, &mut optscoredata, &mut solved, session) {
+                                history.extend(before);
+                            }
+                            if session.done() {
+                                return None;
+                            }
+                        }
+                    },
+                    Err(err) => println!("{}", Red.paint(err))
+                }
+                if solved.len() == vocaitems.len() {
+                    break;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+    None
+}
+
+
+
+///Prints the grid of the memory game, face-down cards show their coordinates
+fn drawmemory(vocaitems: &[&VocaItem], cards: &[(usize, bool)], columns: usize, fields: &FieldPair, solved: &[usize], flipped: &[usize]) {
+    let cellwidth = cards.iter()
+        .map(|(index, isanswer)| if *isanswer { fields.answer(vocaitems[*index]) } else { fields.prompt(vocaitems[*index]) }.chars().count())
+        .max().unwrap_or(0).clamp(4, 20);
+    print!("   ");
+    for column in 0..columns {
+        print!(" {:^width$} ", column + 1, width = cellwidth);
+    }
+    println!();
+    for (row, rowcards) in cards.chunks(columns).enumerate() {
+        print!("{:>2} ", matchlabel(row));
+        for (column, (index, isanswer)) in rowcards.iter().enumerate() {
+            let cardindex = row * columns + column;
+            if solved.contains(&cardindex) || flipped.contains(&cardindex) {
+                let text = if *isanswer { fields.answer(vocaitems[*index]) } else { fields.prompt(vocaitems[*index]) };
+                let cell = format!("{:width$}", truncate(&text, cellwidth), width = cellwidth);
+                if solved.contains(&cardindex) {
+                    print!("[{}]", Green.paint(cell));
+                } else {
+                    print!("[{}]", Blue.paint(cell));
+                }
+            } else {
+                print!("[{:^width$}]", format!("{}{}", matchlabel(row), column + 1), width = cellwidth);
+            }
+        }
+        println!();
+    }
+}
+
+///A turn in the memory game that can be undone: the solved, missed and seen cards before it,
+///and the answers recorded in it
+type MemoryTurn<'a> = (Vec<usize>, HashSet<usize>, HashSet<usize>, Vec<Answer<'a>>);
+
+///Memory (concentration) game: find the pairs of prompts and answers among face-down cards. An item
+///counts as correct if its pair was found without first turning one of its cards in vain when the
+///matching card had already been seen.
+fn memory(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, paircount: usize, fields: &FieldPair, filtertags: Option<&Vec<&str>>, session: &mut Session) {
+    println!("MEMORY (turn two cards by entering their coordinates, e.g. a1 b3, S, B or K and a card (e.g. S a1) to suspend, bury or mark its item as known, U to undo the last turn, enter q to quit)");
+    println!("---------------------------------------------------------------------------------");
+    //we can't draw more distinct items than there are
+    let available: HashSet<String> = data.available(optscoredata.as_deref(), filtertags, |item| fields.applies(item)).iter().map(|item| item.id_as_string()).collect();
+    let paircount = std::cmp::min(paircount, available.len());
+    if paircount == 0 {
+        eprintln!("No items to play with");
+        return;
+    }
+    let interactive = termion::is_tty(&std::io::stdout());
+    while !session.done() {
+        let vocaitems = data.pickdistinct(optscoredata.as_deref_mut(), filtertags, paircount, true, |item| fields.applies(item));
+        //every item has two cards: the prompt and the answer
+        let mut cards: Vec<(usize, bool)> = (0..vocaitems.len()).flat_map(|index| vec![(index, false), (index, true)]).collect();
+        thread_rng().shuffle(&mut cards);
+        let columns = std::cmp::min(4, cards.len());
+        let mut solved: Vec<usize> = Vec::new();
+        let mut missed: HashSet<usize> = HashSet::new();
+        let mut seen: HashSet<usize> = HashSet::new();
+        let mut turns = 0;
+        //the state before every turn along with the answers recorded in it, so turns can be undone
+        let mut history: Vec<MemoryTurn> = Vec::new();
+        //the cards of excluded items are taken out of the game, an undo does not bring them back
+        let mut excluded: Vec<usize> = Vec::new();
+        while solved.len() < cards.len() {
+            //the time for a match is the time the turn took
+            let asked = Instant::now();
+            let mut flipped: Vec<usize> = Vec::new();
+            while flipped.len() < 2 {
+                drawmemory(&vocaitems, &cards, columns, fields, &solved, &flipped);
+                let response = match getinputline() {
+                    Some(response) => response,
+                    None => continue
+                };
+                if response == "q" {
+                    return;
+                } else if response == "U" {
+                    match history.pop() {
+                        Some((previoussolved, previousmissed, previousseen, answers)) => {
+                            for answer in answers.into_iter().rev() {
+                                answer.undo(optscoredata.as_deref_mut(), session);
+                            }
+                            solved = previoussolved;
+                            for cardindex in excluded.iter() {
+                                if !solved.contains(cardindex) {
+                                    solved.push(*cardindex);
+                                }
+                            }
+                            missed = previousmissed;
+                            seen = previousseen;
+                            turns -= 1;
+                            flipped.clear();
+                            println!("{}", Blue.paint("Undid the last turn"));
+                        },
+                        None => println!("{}", Red.paint("Nothing to undo"))
+                    }
+                    continue;
+                } else if let Some((command, target)) = parseexclusion(&response) {
+                    let cardindex = match parsematchresponse(target).as_deref() {
+                        Ok([(column, row)]) if *column < columns && row * columns + column < cards.len() => Some(row * columns + column),
+                        _ => None
+                    };
+                    match cardindex.filter(|cardindex| !solved.contains(cardindex)) {
+                        Some(cardindex) => {
+                            let index = cards[cardindex].0;
+                            if let Some(message) = optscoredata.as_deref_mut().and_then(|scoredata| excludeitem(command, vocaitems[index], scoredata)) {
+                                println!("{}", Blue.paint(format!("{}: {}", fields.prompt(vocaitems[index]), message)));
+                                //both cards of the item are taken out of the game
+                                for (cardindex, card) in cards.iter().enumerate() {
+                                    if card.0 == index {
+                                        solved.push(cardindex);
+                                        excluded.push(cardindex);
+                                    }
+                                }
+                                flipped.retain(|cardindex| cards[*cardindex].0 != index);
+                            }
+                        },
+                        None => println!("{}", Red.paint(format!("No such card: {}", target)))
+                    }
+                    if solved.len() == cards.len() {
+                        break;
+                    }
+                    continue;
+                }
+                match parsematchresponse(&response) {
+                    Ok(coordinates) => {
+                        for (column, row) in coordinates {
+                            let cardindex = row * columns + column;
+                            if column >= columns || cardindex >= cards.len() {
+                                println!("{}", Red.paint(format!("No such card: {}{}", matchlabel(row), column + 1)));
+                            } else if solved.contains(&cardindex) || flipped.contains(&cardindex) {
+                                println!("{}", Red.paint(format!("{}{} is already face up", matchlabel(row), column + 1)));
+                            } else if flipped.len() < 2 {
+                                flipped.push(cardindex);
+                            }
+                        }
+                    },
+                    Err(_) => println!("{}", Red.paint("Expected coordinates of a letter and a number (for example: a1)"))
+                }
+            }
+            if flipped.len() < 2 {
+                //the remaining cards were taken out of the game
+                continue;
+            }
+            turns += 1;
+            let mut turn = (solved.clone(), missed.clone(), seen.clone(), Vec::new());
+            drawmemory(&vocaitems, &cards, columns, fields, &solved, &flipped);
+            let (first, second) = (cards[flipped[0]], cards[flipped[1]]);
+            //cards with identical answers are interchangeable
+            let matched = first.1 != second.1 && fields.answer(vocaitems[first.0]) == fields.answer(vocaitems[second.0]);
+            if matched {
+                println!("{}", Green.paint("A pair!"));
+                //only the two flipped cards are solved, the answer is scored for the item of the
+                //prompt card (with identical answers that need not be the item of the answer card)
+                solved.push(flipped[0]);
+                solved.push(flipped[1]);
+                let index = if first.1 { second.0 } else { first.0 };
+                let correct = !missed.contains(&index);
+                turn.3.push(Answer::new(vocaitems[index], optscoredata.as_deref(), session));
+                if let Some(ref mut scoredata) = optscoredata {
+                    scoredata.addtimedscore(vocaitems[index], correct, asked.elapsed().as_millis() as u64);
+                }
+                session.record(vocaitems[index], correct, optscoredata.as_deref());
+            } else {
+                println!("{}", Red.paint("No match"));
+                //a miss counts against an item if the matching card had been seen already
+                for (cardindex, (index, isanswer)) in [(flipped[0], first), (flipped[1], second)].iter() {
+                    let partner = cards.iter().position(|card| *card == (*index, !*isanswer)).expect("partner card must exist");
+                    if seen.contains(&partner) && !flipped.contains(&partner) {
+                        missed.insert(*index);
+                    }
+                    seen.insert(*cardindex);
+                }
+            }
+            history.push(turn);
+            if session.done() {
+                return;
+            }
+            if !matched && interactive {
+                //hide the cards again before the next turn
+                print!("Press ENTER to continue");
+                std::io::stdout().flush().unwrap();
+                let mut line = String::new();
+                std::io::stdin().read_line(&mut line).unwrap();
+                print!("{}{}", termion::clear::All, termion::cursor::Goto(1,1));
+            }
+        }
+        println!("All {} pairs found in {} turns", vocaitems.len(), turns);
+        println!();
+    }
+}
+
+///Returns the clue for an item in a puzzle; an example is turned into a cloze so it doesn't give
+///away the answer
+fn getclue(vocaitem: &VocaItem, cluefield: &str) -> Option<String> {
+    if cluefield == "example" {
+        vocaitem.cloze().map(|(question, _)| question)
+    } else {
+        vocaitem.field(cluefield).filter(|clue| !clue.is_empty())
+    }
+}
+
+///Returns the file the answer key of a printable puzzle is written to (e.g. puzzle.key.svg for puzzle.svg)
+fn getkeyfile(output: &Path) -> PathBuf {
+    output.with_extension(match output.extension() {
+        Some(extension) => format!("key.{}", extension.to_str().unwrap()),
+        None => "key".to_string()
+    })
+}
+
+///Parses a crossword response like 1a word, 12d word or 3 across word into (number, across, answer)
+fn parsecrosswordresponse(response: &str) -> Option<(usize, bool, String)> {
+    let response = response.trim();
+    let numberlength = response.chars().take_while(|c| c.is_ascii_digit()).count();
+    let number: usize = response[..numberlength].parse().ok()?;
+    let rest = response[numberlength..].trim_start();
+    let directionlength = rest.chars().take_while(|c| c.is_alphabetic()).map(|c| c.len_utf8()).sum();
+    let across = match rest[..directionlength].to_lowercase().as_str() {
+        "a" | "across" => true,
+        "d" | "down" => false,
+        _ => return None
+    };
+    let answer = rest[directionlength..].trim();
+    if answer.is_empty() {
+        None
+    } else {
+        Some((number, across, answer.to_string()))
+    }
+}
+
+///Crossword puzzle in the terminal: solve the clues by entering the number, the direction and the
+///word. Items solved at the first attempt count as correct.
+fn crossword(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, count: usize, cluefield: &str, filtertags: Option<&Vec<&str>>, session: &mut Session) {
+    let instructions = "enter a number, a for across or d for down, and the word (e.g. 1a chat), S, B or K and a number and direction (e.g. S 1a) to suspend, bury or mark an item as known, U to undo the last entry, q to quit, ENTER to give up";
+    println!("CROSSWORD ({})", instructions);
+    println!("---------------------------------------------------------------------------------");
+    let available: HashSet<String> = data.available(optscoredata.as_deref(), filtertags, |item| getclue(item, cluefield).is_some()).iter().map(|item| item.id_as_string()).collect();
+    //draw more items than we need, not all of them will fit in the grid
+    let drawcount = std::cmp::min(count * 2, available.len());
+    while !session.done() {
+        //only the items that end up in the grid are marked as seen
+        let vocaitems = data.pickdistinct(optscoredata.as_deref_mut(), filtertags, drawcount, false, |item| getclue(item, cluefield).is_some());
+        let clues: Vec<String> = vocaitems.iter().map(|item| getclue(item, cluefield).unwrap_or_default()).collect();
+        let puzzle = Crossword::generate(&vocaitems, &clues, count);
+        if puzzle.entries.len() < 2 {
+            eprintln!("Unable to build a crossword from these items");
+            return;
+        }
+        for entry in puzzle.entries.iter() {
+            if let Some(ref mut scoredata) = optscoredata {
+                scoredata.seen(vocaitems[entry.index]);
+            }
+        }
+        let mut revealed: Vec<bool> = vec![false; puzzle.entries.len()];
+        let mut attempts: Vec<u32> = vec![0; puzzle.entries.len()];
+        //the entries made, with the state before solving them, so they can be undone
+        let mut history: Vec<(usize, Option<Answer>)> = Vec::new();
+        while revealed.iter().any(|r| !r) {
+            print!("{}", puzzle.totext(&revealed));
+            for (label, across) in [("ACROSS", true), ("DOWN", false)].iter() {
+                println!("{}", Blue.paint(*label));
+                for (entry, _) in puzzle.entries.iter().zip(revealed.iter()).filter(|(entry, revealed)| entry.across == *across && !**revealed) {
+                    println!("{:>3}. {} ({})", entry.number, entry.clue, entry.answer.chars().count());
+                }
+            }
+            //the time for an entry is the time since the puzzle was last shown
+            let asked = Instant::now();
+            let response = match getinputline() {
+                Some(response) => response,
+                None => {
+                    //give up, the remaining entries count as incorrect
+                    for (i, entry) in puzzle.entries.iter().enumerate() {
+                        if revealed[i] {
+                            continue;
+                        }
+                        if let Some(ref mut scoredata) = optscoredata {
+                            scoredata.addscore(vocaitems[entry.index], false);
+                        }
+                        session.record(vocaitems[entry.index], false, optscoredata.as_deref());
+                        revealed[i] = true;
+                    }
+                    break;
+                }
+            };
+            if response == "q" {
+                return;
+            } else if response == "h" {
+                println!("{}", instructions);
+                continue;
+            } else if response == "U" {
+                match history.pop() {
+                    Some((i, Some(answer))) => {
+                        answer.undo(optscoredata.as_deref_mut(), session);
+                        revealed[i] = false;
+                        println!("{}", Blue.paint(format!("Undid the answer for {}{}", puzzle.entries[i].number, if puzzle.entries[i].across { "a" } else { "d" })));
+                    },
+                    Some((i, None)) => {
+                        attempts[i] -= 1;
+                        println!("{}", Blue.paint(format!("Undid the attempt for {}{}", puzzle.entries[i].number, if puzzle.entries[i].across { "a" } else { "d" })));
+                    },
+                    None => println!("{}", Red.paint("Nothing to undo"))
+                }
+                continue;
+            } else if let Some((command, target)) = parseexclusion(&response) {
+                let target = target.replace(' ', "").to_lowercase();
+                match puzzle.entries.iter().position(|entry| format!("{}{}", entry.number, if entry.across { "a" } else { "d" }) == target).filter(|i| !revealed[*i]) {
+                    Some(i) => {
+                        let vocaitem = vocaitems[puzzle.entries[i].index];
+                        if let Some(message) = optscoredata.as_deref_mut().and_then(|scoredata| excludeitem(command, vocaitem, scoredata)) {
+                            //the entry is filled in, without a score
+                            println!("{}", Blue.paint(format!("{}: {}", puzzle.entries[i].answer, message)));
+                            revealed[i] = true;
+                        }
+                    },
+                    None => println!("{}", Red.paint(format!("There is no unsolved {}", target)))
+                }
+                continue;
+            }
+            let (number, across, answer) = match parsecrosswordresponse(&response) {
+                Some(parsed) => parsed,
+                None => {
+                    println!("{}", Red.paint("Expected a number, a direction and a word (for example: 1a chat)"));
+                    continue;
+                }
+            };
+            let i = match puzzle.entries.iter().position(|entry| entry.number == number && entry.across == across) {
+                Some(i) => i,
+                None => {
+                    println!("{}", Red.paint(format!("There is no {} {}", number, if across { "across" } else { "down" })));
+                    continue;
+                }
+            };
+            if revealed[i] {
+                println!("{}", Red.paint("That one was already solved!"));
+                continue;
+            }
+            let vocaitem = vocaitems[puzzle.entries[i].index];
+            if Crossword::normalise(&answer) == puzzle.entries[i].answer {
+                println!("{}", Green.paint("Correct!"));
+                revealed[i] = true;
+                let correct = attempts[i] == 0;
+                history.push((i, Some(Answer::new(vocaitem, optscoredata.as_deref(), session))));
+                if let Some(ref mut scoredata) = optscoredata {
+                    scoredata.addtimedscore(vocaitem, correct, asked.elapsed().as_millis() as u64);
+                }
+                session.record(vocaitem, correct, optscoredata.as_deref());
+                if session.done() {
+                    return;
+                }
+            } else {
+                println!("{}", Red.paint("Incorrect!"));
+                attempts[i] += 1;
+                history.push((i, None));
+            }
+        }
+        print!("{}", puzzle.totext(&revealed));
+        println!("{}", puzzle.answerstext());
+    }
+}
+
+///Changes to the directory a saved game was started in, so relative paths in its command line
+///still refer to the same files
+fn enterdirectory(game: &SavedGame) {
+    if let Some(ref directory) = game.directory {
+        if let Err(err) = std::env::set_current_dir(directory) {
+            eprintln!("Unable to change to {}, where the game was started: {}", directory.to_str().unwrap_or_default(), err);
+        }
+    }
+}
+
+///Saves an interrupted game. There is only one saved game, so unless the game being saved was
+///resumed from it, this asks before replacing another interrupted game. Returns false if the game
+///was not saved.
+fn savegame(mut game: SavedGame, savedgamefile: &Path, resumed: bool) -> bool {
+    if !resumed {
+        if let Ok(other) = SavedGame::load(savedgamefile.to_str().expect("Invalid session file")) {
+            if !confirm(&format!("This replaces the interrupted game ({}), save anyway?", other.describe()), false) {
+                return false;
+            }
+        }
+    }
+    //relative paths on the command line are resolved from the directory the game was started in
+    game.directory = std::env::current_dir().ok();
+    game.save(savedgamefile.to_str().expect("Invalid session file")).expect("Unable to save session file");
+    true
+}
+
+///A vocabulary list taking part in the daily review, along with its scores
+struct ReviewList {
+    data: VocaList,
+    scoredata: VocaScore,
+    scorefile: PathBuf,
+    template: Option<CardTemplate>,
+}
+
+///Load the given data files, several lists are merged into one (weighted by file name)
+fn loaddata(datafiles: &[String], weights: &HashMap<String, f64>) -> Result<VocaList, Box<dyn std::error::Error>> {
+    if datafiles.len() == 1 {
+        return VocaList::parse(&datafiles[0]);
+    }
+    let mut lists: Vec<(VocaList, f64)> = Vec::new();
+    //a word that occurs in several lists is only practised (and scored) as part of the first
+    let mut owners: HashMap<String, &str> = HashMap::new();
+    for datafile in datafiles.iter() {
+        let name = Path::new(datafile).file_stem().unwrap().to_str().unwrap();
+        let weight = weights.get(name).or_else(|| weights.get(datafile.strip_suffix(".json").unwrap_or(datafile))).cloned().unwrap_or(1.0);
+        let list = VocaList::parse(datafile)?;
+        for item in list.items.iter() {
+            let owner = *owners.entry(item.id_as_string()).or_insert(name);
+            if owner != name {
+                eprintln!("({} occurs in both {} and {}, it is only practised as part of {})", item.word, owner, name, owner);
+            }
+        }
+        lists.push((list, weight));
+    }
+    Ok(VocaList::merge(lists))
+}
+
+///A review that can be undone: the queue entry, its grade, and the item scores, session and
+///statistics before it
+type ReviewUndo = ((usize, usize, bool), Grade, VocaScore, Session, ReviewStats);
+
+///Daily review over all lists: due items and a limited number of new items, graded like
+///flashcards. Items graded 'again' come back later in the same review.
+fn review(lists: &mut [ReviewList], stats: &mut ReviewStats, maxnew: usize, maxreviews: usize, phon: bool, session: &mut Session) {
+    let today = stats.todaystats();
+    let remaining = maxreviews.saturating_sub(today.reviews as usize);
+    //queue entries are (list index, item index, new)
+    let mut due: Vec<(usize, usize, bool, u64)> = Vec::new();
+    let mut new: Vec<Vec<(usize, usize, bool, u64)>> = Vec::new();
+    for (l, list) in lists.iter().enumerate() {
+        let mut listnew = Vec::new();
+        for (i, item) in list.data.items.iter().enumerate() {
+            if list.scoredata.excluded(item) {
+                continue;
+            } else if list.scoredata.isdue(item) {
+                due.push((l, i, false, *list.scoredata.due.get(&item.id_as_string()).unwrap_or(&0)));
+            } else if !list.scoredata.lastseen.contains_key(&item.id_as_string()) {
+                listnew.push((l, i, true, 0));
+            }
+        }
+        new.push(listnew);
+    }
+    //most overdue first
+    due.sort_by_key(|(_, _, _, due)| *due);
+    due.truncate(remaining);
+    //take new items from all lists in turn
+    let newcount = std::cmp::min(maxnew.saturating_sub(today.new as usize), remaining - due.len());
+    let mut queue: Vec<(usize, usize, bool)> = due.into_iter().map(|(l, i, new, _)| (l, i, new)).collect();
+    let duecount = queue.len();
+    let mut round = 0;
+    while queue.len() < duecount + newcount && new.iter().any(|listnew| round < listnew.len()) {
+        for listnew in new.iter() {
+            if let Some((l, i, new, _)) = listnew.get(round) {
+                if queue.len() < duecount + newcount {
+                    queue.push((*l, *i, *new));
+                }
+            }
+        }
+        round += 1;
+    }
+    thread_rng().shuffle(&mut queue);
+    let mut queue: std::collections::VecDeque<(usize, usize, bool)> = queue.into_iter().collect();
+
+    let instructions = "type ENTER to turn, q to quit, grade with 1 (again), 2 (hard), 3 (good) or 4 (easy); k and i are aliases for good and again; S to suspend, B to bury, K to mark as known, U to undo the last answer";
+    println!("REVIEW ({} due, {} new; {})", duecount, queue.len() - duecount, instructions);
+    println!("---------------------------------------------------------------------------------------");
+    if queue.is_empty() {
+        println!("Nothing to review today!");
+    }
+    //every review along with the state before it, so reviews can be undone
+    let mut history: Vec<ReviewUndo> = Vec::new();
+    while let Some((l, i, new)) = queue.pop_front() {
+        if session.done() {
+            break;
+        }
+        let list = &mut lists[l];
+        let vocaitem = &list.data.items[i];
+        let mut undo = false;
+        let mut turned = false;
+        let shown = Instant::now();
+        let mut recalltime: Option<u64> = None;
+        let grade = loop {
+            match (&list.template, turned) {
+                (Some(template), false) => println!("[{} left] {}", queue.len() + 1, template.front(vocaitem)),
+                (Some(template), true) => println!("{}", template.back(vocaitem)),
+                (None, false) => {
+                    print!("[{} left] ", queue.len() + 1);
+                    quizprompt(vocaitem, phon);
+                },
+                (None, true) => {
+                    println!("{}", vocaitem.transcription);
+                    println!("{}", vocaitem.translation);
+                    println!("{}", vocaitem.plainexample());
+                }
+            }
+            match getinputkey() {
+                Some(response) => {
+                    if let Some(message) = excludeitem(&response, vocaitem, &mut list.scoredata) {
+                        println!("{}", Blue.paint(message));
+                        break None;
+                    } else if let Some(grade) = Grade::parse(&response) {
+                        break Some(grade);
+                    } else if response == "U" && history.is_empty() {
+                        println!("{}", Red.paint("Nothing to undo"));
+                    } else if response == "U" {
+                        undo = true;
+                        break None;
+                    } else if response == "h" {
+                        println!("{}", instructions);
+                    } else if response == "q" {
+                        return;
+                    } else {
+                        println!("{}", Red.paint("Invalid input"));
+                    }
+                },
+                None => {
+                    turned = !turned;
+                    recalltime.get_or_insert(shown.elapsed().as_millis() as u64);
+                }
+            }
+        };
+        let grade = match grade {
+            Some(grade) => grade,
+            None if undo => {
+                //review the undone item again, then this one
+                let ((undonel, undonei, undonenew), undonegrade, scoredata, previoussession, previousstats) = history.pop().expect("history can't be empty");
+                let undonelist = &mut lists[undonel];
+                undonelist.scoredata.updateitem(&scoredata, &undonelist.data.items[undonei]);
+                *session = previoussession;
+                *stats = previousstats;
+                if undonegrade == Grade::Again {
+                    if let Some(position) = queue.iter().rposition(|entry| *entry == (undonel, undonei, false)) {
+                        queue.remove(position);
+                    }
+                }
+                queue.push_front((l, i, new));
+                queue.push_front((undonel, undonei, undonenew));
+                println!("{}", Blue.paint(format!("Undid the review of {}, try again", undonelist.data.items[undonei].word)));
+                println!();
+                continue;
+            },
+            None => {
+                println!();
+                continue;
+            }
+        };
+        history.push(((l, i, new), grade, list.scoredata.snapshot(vocaitem), session.clone(), stats.clone()));
+        list.scoredata.addtimedgrade(vocaitem, grade, recalltime.unwrap_or(shown.elapsed().as_millis() as u64));
+        session.record(vocaitem, grade.correct(), Some(&list.scoredata));
+        stats.record(new, grade.correct());
+        if grade == Grade::Again {
+            //try again later today
+            queue.push_back((l, i, false));
         }
         println!();
     }
+    if queue.is_empty() {
+        println!("{}", Green.paint("All done for today!"));
+    }
 }
 
-fn parsematchresponse(vocaitems: &Vec<&VocaItem>, mappings: &Vec<u8>, response: String, optscoredata: &mut Option<&mut VocaScore>, solved: &mut Vec<u8>) -> bool {
-    let bytes: Vec<u8> = response.into_bytes();
-    if let (Some(first), Some(second)) = (bytes.get(0), bytes.get(1)) {
-        let firstchar: char = *first as char;
-        let first: u8 = *first - 0x31u8;
-        let secondchar: char = *second as char;
-        let second: u8 = *second - 0x61u8;
-        if firstchar.is_ascii_digit() {
-            if secondchar.is_ascii_alphabetic() {
-                //println!("{}@{} in {:?}.. solved={:?}", (first as usize), (second as usize),mappings, solved); //DEBUG
-                if let Some(mapped) = mappings.get(second as usize) {
-                    if !solved.contains(&first) {
-                        let correct: bool = *mapped == first;
-                        if correct {
-                            solved.push(first);
-                            println!("{}", Green.paint("Correct!"));
-                        } else {
-                            println!("{}", Red.paint("Wrong!"));
-                        }
-                        if let Some(ref mut scoredata) = optscoredata {
-                            if let Some(vocaitem) = vocaitems.get(first as usize) {
-                                scoredata.addscore(vocaitem, correct);
-                            }
-                        }
-                        return true;
-                    } else {
-                        println!("{}", Red.paint("This one was already solved!"));
-                    }
+///Prints the review streak and the history of the last two weeks
+fn reviewstats(stats: &ReviewStats) {
+    println!("Current streak: {} days (longest: {} days)", stats.currentstreak(), stats.longeststreak);
+    let today = ReviewStats::today();
+    for day in today.saturating_sub(13)..=today {
+        let daystats = stats.history.get(&day).cloned().unwrap_or_default();
+        let daysago = match today - day {
+            0 => "today".to_string(),
+            1 => "yesterday".to_string(),
+            n => format!("{} days ago", n),
+        };
+        println!("  {:<12} {:>4} reviews, {:>3} new, {:>3}% correct", daysago, daystats.reviews, daystats.new, (daystats.correct * 100).checked_div(daystats.reviews).unwrap_or(0));
+    }
+}
+
+///Exam: a fixed sample of questions without feedback or hints, graded at the end. Returns false
+///if the exam was paused before the end.
+fn exam(items: &[&VocaItem], record: &mut ExamRecord, phon: bool) -> bool {
+    println!("EXAM ({} questions, type the translation and ENTER, ENTER to leave a question blank, q to pause (resume later with vj resume))", items.len());
+    println!("---------------------------------------------------------------------------------");
+    //questions that were already answered before the exam was paused are skipped
+    for (i, vocaitem) in items.iter().enumerate().skip(record.questions.len()) {
+        print!("{}/{} ", i+1, items.len());
+        quizprompt(vocaitem, phon);
+        let answer = getinputline().unwrap_or_default();
+        if answer == "q" {
+            return false;
+        }
+        let correct = !answer.is_empty() && checktranslation(&answer, &vocaitem.translation);
+        record.questions.push(ExamQuestion {
+            word: vocaitem.word.clone(),
+            answer,
+            reference: vocaitem.translation.clone(),
+            correct,
+        });
+    }
+    println!();
+    println!("EXAM REPORT");
+    println!("---------------------------------------------------------------------------------");
+    for (i, question) in record.questions.iter().enumerate() {
+        let mark = match question.correct {
+            true => Green.paint("correct"),
+            false => Red.paint("incorrect"),
+        };
+        println!("{}. {} -> {} ({}; the correct translation is: {})", i+1, question.word, question.answer, mark, question.reference);
+    }
+    println!("---------------------------------------------------------------------------------");
+    println!("Grade: {}/{} ({:.0}%)", record.correct(), items.len(), record.grade());
+    true
+}
+
+///Views in the full-screen terminal interface
+#[derive(PartialEq)]
+enum TuiView {
+    Browse,
+    Card
+}
+
+///State of the full-screen terminal interface
+struct TuiState<'a> {
+    items: Vec<&'a VocaItem>,
+    view: TuiView,
+    selected: usize,
+    offset: usize,
+    card: Option<&'a VocaItem>,
+    turned: bool,
+    /// When the current card was drawn (None if it was looked up rather than drawn)
+    shown: Option<Instant>,
+    /// How long it took to turn the current card (in milliseconds)
+    recalltime: Option<u64>,
+    phon: bool,
+    seen: u32,
+    message: String,
+    /// The state before the current card was drawn, so its answer can be undone
+    before: Option<Answer<'a>>,
+}
+
+///Draws a new card in the full-screen terminal interface
+fn nextcard<'a>(state: &mut TuiState<'a>, data: &'a VocaList, mut optscoredata: Option<&mut VocaScore>, filtertags: Option<&Vec<&str>>, session: &Session) {
+    state.card = data.pick(optscoredata.as_deref_mut(), filtertags, false);
+    state.before = state.card.map(|item| Answer::new(item, optscoredata.as_deref(), session));
+    if let (Some(item), Some(scoredata)) = (state.card, optscoredata) {
+        scoredata.seen(item);
+    }
+    state.turned = false;
+    state.shown = Some(Instant::now());
+    state.recalltime = None;
+    state.seen += 1;
+}
+
+///Truncate a string to fit in the given number of characters
+fn truncate(s: &str, width: usize) -> String {
+    s.chars().take(width).collect()
+}
+
+///Renders a progress bar of the given width
+fn progressbar(done: usize, total: usize, width: usize) -> String {
+    let filled = (done * width).checked_div(total).unwrap_or(0);
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(width - filled))
+}
+
+///Draws the full-screen terminal interface
+fn drawtui(screen: &mut impl Write, state: &TuiState, optscoredata: Option<&VocaScore>, session: &Session) -> std::io::Result<()> {
+    let (width, height) = termion::terminal_size()?;
+    let (width, height) = (width as usize, height as usize);
+    write!(screen, "{}", termion::clear::All)?;
+    if width < 20 || height < 6 {
+        write!(screen, "{}Terminal too small", termion::cursor::Goto(1,1))?;
+        return screen.flush();
+    }
+    let panelwidth = 32;
+    let mainwidth = if width > panelwidth + 20 { width - panelwidth - 1 } else { width };
+    let mainheight = height - 4;
+
+    //title bar
+    let title = match state.view {
+        TuiView::Browse => "VOCAJEUX - browse",
+        TuiView::Card => "VOCAJEUX - cards",
+    };
+    write!(screen, "{}{}{}{}", termion::cursor::Goto(1,1), termion::style::Invert, truncate(&format!("{:width$}", title, width = width), width), termion::style::Reset)?;
+
+    //main area
+    match state.view {
+        TuiView::Browse => {
+            for (row, (i, item)) in state.items.iter().enumerate().skip(state.offset).take(mainheight).enumerate() {
+                let line = if state.phon {
+                    format!("{} ({})  {}", item.word, item.transcription, item.translation)
+                } else {
+                    format!("{}  {}", item.word, item.translation)
+                };
+                write!(screen, "{}", termion::cursor::Goto(1, (row + 2) as u16))?;
+                if i == state.selected {
+                    write!(screen, "{}{}{}", termion::style::Invert, truncate(&line, mainwidth), termion::style::Reset)?;
+                } else {
+                    write!(screen, "{}", truncate(&line, mainwidth))?;
+                }
+            }
+        },
+        TuiView::Card => {
+            if let Some(item) = state.card {
+                let mut lines: Vec<String> = vec!(format!("{}", Blue.paint(item.word.as_str())));
+                if state.phon || state.turned {
+                    lines.push(item.transcription.clone());
+                }
+                if state.turned {
+                    lines.push(format!("{}", Green.paint(item.translation.as_str())));
+                    lines.push(item.plainexample());
+                    lines.push(item.comment.clone());
+                    lines.push(item.tags.join(", "));
                 } else {
-                    eprintln!("{}", Red.paint("Invalid input"));
+                    lines.push(item.plainexample());
+                }
+                for (row, line) in lines.iter().filter(|line| !line.is_empty()).take(mainheight).enumerate() {
+                    write!(screen, "{}{}", termion::cursor::Goto(3, (row + 3) as u16), line)?;
                 }
             } else {
-                println!("{}", Red.paint("Expected a letter in the second position (for example: 1a)"));
+                write!(screen, "{}No items left to practise", termion::cursor::Goto(3, 3))?;
             }
-        } else {
-            println!("{}", Red.paint("Expected a digit in the first position (for example: 1a)"));
         }
     }
-    false
-}
 
-///Match quiz
-fn matchquiz(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, matchcount: u8, phon: bool, filtertags: Option<&Vec<&str>>) {
-    println!("MATCH QUIZ (Enter a match by entering a number and a letter, enter q to quit, ENTER to skip)");
-    println!("----------------------------------------------------------------------------------------");
-    loop {
-        let mut vocaitems: Vec<&VocaItem> = Vec::new();
-        for _i in 0..matchcount {
-            let vocaitem;
-            if let Some(ref mut scoredata) = optscoredata {
-                vocaitem = data.pick(Some(scoredata), filtertags, true);
-            } else {
-                vocaitem = data.pick(None, filtertags, true);
+    //statistics panel
+    if mainwidth < width {
+        let accuracy = if session.practised > 0 {
+            format!("{:.0}%", 100.0 * session.accuracy())
+        } else {
+            "-".to_string()
+        };
+        let mut lines: Vec<String> = vec!(
+            "SESSION".to_string(),
+            format!("Seen:      {}", state.seen),
+            format!("Correct:   {}", session.correct),
+            format!("Incorrect: {}", session.incorrect),
+            format!("Accuracy:  {}", accuracy),
+        );
+        let current = match state.view {
+            TuiView::Browse => state.items.get(state.selected).copied(),
+            TuiView::Card => state.card,
+        };
+        if let (Some(scoredata), Some(item)) = (optscoredata, current) {
+            let id = item.id_as_string();
+            lines.push(String::new());
+            lines.push("ITEM".to_string());
+            lines.push(format!("Correct:   {}", scoredata.correct.get(&id).unwrap_or(&0)));
+            lines.push(format!("Incorrect: {}", scoredata.incorrect.get(&id).unwrap_or(&0)));
+            lines.push(format!("Ease:      {:.2}", scoredata.ease.get(&id).unwrap_or(&DEFAULT_EASE)));
+            if let Some(grade) = scoredata.grade.get(&id) {
+                lines.push(format!("Grade:     {}", grade));
             }
-            vocaitems.push(vocaitem);
+            if scoredata.suspended.contains_key(&id) {
+                lines.push("Status:    suspended".to_string());
+            } else if scoredata.known.contains_key(&id) {
+                lines.push("Status:    known".to_string());
+            } else if scoredata.excluded(item) {
+                lines.push("Status:    buried".to_string());
+            }
+        }
+        for (row, line) in lines.iter().take(mainheight).enumerate() {
+            write!(screen, "{}|{}", termion::cursor::Goto((mainwidth + 1) as u16, (row + 2) as u16), truncate(line, panelwidth))?;
         }
-        //create a random order for presentation of the translations
-        //values correspond to indices in vocaitems
-        let mut mappings: Vec<u8> = (0..matchcount).collect();
-        thread_rng().shuffle(&mut mappings);
-        let mut solved: Vec<u8> = Vec::new();
-        let mut solvedanswers: Vec<u8> = Vec::new();
+    }
+
+    //progress bar: items that are answered correctly more often than not
+    if let Some(scoredata) = optscoredata {
+        let known = state.items.iter().filter(|item| {
+            let id = item.id_as_string();
+            scoredata.correct.get(&id).unwrap_or(&0) > scoredata.incorrect.get(&id).unwrap_or(&0)
+        }).count();
+        let barwidth = width.saturating_sub(30).max(1);
+        write!(screen, "{}Known {} {}/{}", termion::cursor::Goto(1, (height - 1) as u16), progressbar(known, state.items.len(), barwidth), known, state.items.len())?;
+    }
 
+    //help and message line
+    let help = match state.view {
+        TuiView::Browse => "up/down/j/k: move, ENTER: open card, TAB: practice, q: quit",
+        TuiView::Card => "SPACE: turn, 1-4: grade (again/hard/good/easy), k/i: good/again, S/B/K: suspend/bury/known, U: undo, n: next, TAB: browse, q: quit",
+    };
+    let bottom = if state.message.is_empty() { help.to_string() } else { state.message.clone() };
+    write!(screen, "{}{}", termion::cursor::Goto(1, height as u16), truncate(&bottom, width))?;
+    screen.flush()
+}
 
-        loop {
-            for (i, vocaitem) in vocaitems.iter().enumerate() {
-                if !solved.contains(&(i as u8)) {
-                    if phon {
-                        println!("{}) {} ({})", i+1, vocaitem.word, vocaitem.transcription);
-                    } else {
-                        println!("{}) {}", i+1, vocaitem.word);
-                    }
-                } else {
-                    if let Some(solvedanswer) = mappings.iter().position(|&j| j == i as u8) {
-                        solvedanswers.push(solvedanswer as u8);
-                    }
+///Full-screen terminal interface with a list browser and flashcards
+fn tui(data: &VocaList, mut optscoredata: Option<&mut VocaScore>, phon: bool, filtertags: Option<&Vec<&str>>, session: &mut Session) {
+    if !termion::is_tty(&std::io::stdin()) {
+        eprintln!("The terminal interface requires an interactive terminal");
+        return;
+    }
+    let items: Vec<&VocaItem> = data.items.iter().filter(|item| item.filter(filtertags)).collect();
+    if items.is_empty() {
+        eprintln!("No items to show");
+        return;
+    }
+    let stdout = std::io::stdout().into_raw_mode().expect("Unable to switch the terminal to raw mode");
+    let mut screen = termion::screen::AlternateScreen::from(stdout);
+    write!(screen, "{}", termion::cursor::Hide).unwrap();
+    let mut state = TuiState {
+        items,
+        view: TuiView::Browse,
+        selected: 0,
+        offset: 0,
+        card: None,
+        turned: false,
+        shown: None,
+        recalltime: None,
+        phon,
+        seen: 0,
+        message: String::new(),
+        before: None,
+    };
+    let mut history: Vec<Answer> = Vec::new();
+    let stdin = std::io::stdin();
+    let mut keys = stdin.lock().keys();
+    loop {
+        drawtui(&mut screen, &state, optscoredata.as_deref(), session).expect("Unable to draw on terminal");
+        let key = match keys.next() {
+            Some(Ok(key)) => key,
+            _ => break
+        };
+        state.message.clear();
+        let (_, height) = termion::terminal_size().unwrap_or((80,24));
+        let mainheight = (height as usize).saturating_sub(4).max(1);
+        match (&state.view, key) {
+            (_, Key::Char('q')) | (_, Key::Ctrl('c')) | (_, Key::Esc) => break,
+            (TuiView::Browse, Key::Up) | (TuiView::Browse, Key::Char('k')) => {
+                state.selected = state.selected.saturating_sub(1);
+            },
+            (TuiView::Browse, Key::Down) | (TuiView::Browse, Key::Char('j')) if state.selected + 1 < state.items.len() => {
+                state.selected += 1;
+            },
+            (TuiView::Browse, Key::PageUp) => {
+                state.selected = state.selected.saturating_sub(mainheight);
+            },
+            (TuiView::Browse, Key::PageDown) => {
+                state.selected = std::cmp::min(state.selected + mainheight, state.items.len() - 1);
+            },
+            (TuiView::Browse, Key::Char('\n')) => {
+                let word = state.items[state.selected].word.as_str();
+                state.card = data.find(word, optscoredata.as_deref_mut(), true);
+                state.before = None;
+                state.turned = true;
+                state.shown = None;
+                state.recalltime = None;
+                state.view = TuiView::Card;
+            },
+            (TuiView::Browse, Key::Char('\t')) => {
+                state.view = TuiView::Card;
+                if state.card.is_none() {
+                    nextcard(&mut state, data, optscoredata.as_deref_mut(), filtertags, session);
                 }
-            }
-            println!("{}", Blue.paint("---match with:---"));
-            //println!("{:?}.. solved={:?}", mappings, solved); //DEBUG
-            for (i, mappedindex) in mappings.iter().enumerate() {
-                if !solvedanswers.contains(&(i as u8)) {
-                    if let Some(vocaitem) = vocaitems.get(*mappedindex as usize) {
-                        let c: char = (0x61u8 + i as u8) as char;
-                        println!("{}) {}", c, vocaitem.translation);
-                    }
+            },
+            (TuiView::Card, Key::Char('\t')) | (TuiView::Card, Key::Char('b')) => {
+                state.view = TuiView::Browse;
+            },
+            (TuiView::Card, Key::Char(' ')) | (TuiView::Card, Key::Char('\n')) => {
+                state.turned = !state.turned;
+                if state.recalltime.is_none() {
+                    state.recalltime = state.shown.map(|shown| shown.elapsed().as_millis() as u64);
                 }
-            }
-            //get response from user
-            if let Some(response) = getinputline() {
-                if response == "q" {
-                    return;
-                } else {
-                    if parsematchresponse(&vocaitems, &mappings, response, &mut optscoredata, &mut solved) {
-                        if solved.len() == matchcount as usize {
-                            break;
+            },
+            (TuiView::Card, Key::Char('n')) => {
+                nextcard(&mut state, data, optscoredata.as_deref_mut(), filtertags, session);
+            },
+            (TuiView::Card, Key::Char(c)) if matches!(c, 'S' | 'B' | 'K') && state.card.is_some() && optscoredata.is_some() => {
+                let item = state.card.unwrap();
+                if let Some(message) = optscoredata.as_deref_mut().and_then(|scoredata| excludeitem(&c.to_string(), item, scoredata)) {
+                    state.message = format!("{}: {}", item.word, message);
+                }
+                nextcard(&mut state, data, optscoredata.as_deref_mut(), filtertags, session);
+            },
+            (TuiView::Card, Key::Char('U')) => {
+                match history.pop() {
+                    Some(answer) => {
+                        let item = answer.undo(optscoredata.as_deref_mut(), session);
+                        state.message = format!("Undid the answer for {}", item.word);
+                        state.card = Some(item);
+                        state.before = Some(Answer::new(item, optscoredata.as_deref(), session));
+                        if let Some(ref mut scoredata) = optscoredata {
+                            scoredata.seen(item);
                         }
+                        state.turned = false;
+                        state.shown = Some(Instant::now());
+                        state.recalltime = None;
+                    },
+                    None => state.message = "Nothing to undo".to_string()
+                }
+            },
+            (TuiView::Card, Key::Char(c)) => {
+                if let (Some(grade), Some(item)) = (Grade::parse(&c.to_string()), state.card) {
+                    history.push(state.before.take().unwrap_or_else(|| Answer::new(item, optscoredata.as_deref(), session)));
+                    if let Some(ref mut scoredata) = optscoredata {
+                        match state.recalltime.or_else(|| state.shown.map(|shown| shown.elapsed().as_millis() as u64)) {
+                            Some(millis) => scoredata.addtimedgrade(item, grade, millis),
+                            None => scoredata.addgrade(item, grade)
+                        }
+                    }
+                    session.record(item, grade.correct(), optscoredata.as_deref());
+                    if session.done() {
+                        break;
                     }
+                    state.message = format!("{}: {}", item.word, grade);
+                    nextcard(&mut state, data, optscoredata.as_deref_mut(), filtertags, session);
+                } else {
+                    state.message = "Invalid input".to_string();
                 }
-            } else {
-                break;
-            }
+            },
+            _ => {}
+        }
+        //keep the selection in view
+        if state.selected < state.offset {
+            state.offset = state.selected;
+        } else if state.selected >= state.offset + mainheight {
+            state.offset = state.selected + 1 - mainheight;
         }
     }
+    write!(screen, "{}", termion::cursor::Show).unwrap();
 }
 
 
-
 fn main() {
     let mut success = true; //determines the exit code
     let defaultdatadir = defaultdatadir();
@@ -372,9 +2339,27 @@ fn main() {
                         .help("Vocabulary file to load, either a full path or from ~/.config/vocajeux/data/")
                         .index(1)
                         .required(true);
+    let arg_files = Arg::with_name("file")
+                        .help("Vocabulary file(s) to load, either a full path or from ~/.config/vocajeux/data/, several lists are practised together")
+                        .index(1)
+                        .multiple(true)
+                        .required_unless("all");
+    let arg_all = Arg::with_name("all")
+                        .help("Practise all lists in the data directory")
+                        .long("all")
+                        .short("A")
+                        .conflicts_with("file");
+    let arg_weight = Arg::with_name("weight")
+                        .help("Weight of a list when practising several lists (e.g. french-verbs=2), lists have weight 1 by default")
+                        .long("weight")
+                        .short("W")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1);
     let arg_tags = Arg::with_name("tags")
-                        .help("Filter on tags, comma separated list")
+                        .help("Filter on tags or field values (field=value), comma separated list")
                         .long("tags")
+                        .takes_value(true)
                         .short("T");
     let arg_phon = Arg::with_name("phon")
                          .help("Show phonetic transcription")
@@ -392,7 +2377,31 @@ fn main() {
                          .help("Show comments")
                          .long("comments")
                          .short("C");
-    let argmatches = App::new("Vocajeux")
+    let arg_count = Arg::with_name("count")
+                         .help("End the session after this many items")
+                         .long("count")
+                         .short("c")
+                         .takes_value(true);
+    let arg_minutes = Arg::with_name("minutes")
+                         .help("End the session after this many minutes")
+                         .long("minutes")
+                         .short("M")
+                         .takes_value(true);
+    let arg_summary = Arg::with_name("summary")
+                         .help("Write a summary of the session to this file (JSON)")
+                         .long("summary")
+                         .takes_value(true);
+    let arg_promptfield = Arg::with_name("promptfield")
+                         .help("Field to prompt with (word, transcription, translation, example, comment, tags, or a custom field)")
+                         .long("prompt-field")
+                         .takes_value(true)
+                         .default_value("word");
+    let arg_answerfield = Arg::with_name("answerfield")
+                         .help("Field to answer with (word, transcription, translation, example, comment, tags, or a custom field)")
+                         .long("answer-field")
+                         .takes_value(true)
+                         .default_value("translation");
+    let app = App::new("Vocajeux")
         .version("0.1")
         .author("Maarten van Gompel (proycon) <proycon@anaproy.nl>")
         .about("Games for learning vocabulary")
@@ -424,6 +2433,26 @@ fn main() {
         .subcommand(SubCommand::with_name("ls")
                     .about("Lists all available datasets")
         )
+        .subcommand(SubCommand::with_name("review")
+                    .about("Daily review of the due items (and some new ones) from all lists in the data directory")
+                    .arg(arg_phon.clone())
+                    .arg(Arg::with_name("new")
+                         .help("Maximum number of new items per day")
+                         .long("new")
+                         .short("n")
+                         .takes_value(true)
+                         .default_value("10"))
+                    .arg(Arg::with_name("max")
+                         .help("Maximum number of reviews per day")
+                         .long("max")
+                         .takes_value(true)
+                         .default_value("100"))
+                    .arg(Arg::with_name("stats")
+                         .help("Show the streak and the review history instead of reviewing")
+                         .long("stats"))
+                    .arg(arg_minutes.clone())
+                    .arg(arg_summary.clone())
+        )
         .subcommand(SubCommand::with_name("show")
                     .about("Show the entire vocabulary list")
                     .arg(arg_file.clone())
@@ -435,6 +2464,10 @@ fn main() {
                          .help("Show tags")
                          .long("showtags")
                     )
+                    .arg(Arg::with_name("extra")
+                         .help("Show custom fields")
+                         .long("extra")
+                    )
                     .arg(arg_phon.clone()))
         .subcommand(SubCommand::with_name("csv")
                     .about("Output all data as CSV")
@@ -472,15 +2505,54 @@ fn main() {
                          .long("tags")
                          .takes_value(true)
                          .short("T"))
+                    .arg(Arg::with_name("field")
+                         .help("Custom field (key=value), may be given multiple times")
+                         .long("field")
+                         .short("F")
+                         .takes_value(true)
+                         .multiple(true)
+                         .number_of_values(1))
                     )
+        .subcommand(SubCommand::with_name("learn")
+                    .about("Learn new items: introduce and drill items never seen before, then review")
+                    .arg(arg_files.clone())
+                    .arg(arg_all.clone())
+                    .arg(arg_weight.clone())
+                    .arg(arg_tags.clone())
+                    .arg(arg_phon.clone())
+                    .arg(Arg::with_name("new")
+                         .help("Number of new items to introduce in this session")
+                         .long("new")
+                         .short("n")
+                         .takes_value(true)
+                         .default_value("5"))
+                    .arg(Arg::with_name("daily")
+                         .help("Maximum number of new items to introduce per day")
+                         .long("daily")
+                         .takes_value(true)
+                         .default_value("20"))
+                    .arg(arg_promptfield.clone())
+                    .arg(arg_answerfield.clone())
+                    .arg(arg_count.clone())
+                    .arg(arg_minutes.clone())
+                    .arg(arg_summary.clone()))
         .subcommand(SubCommand::with_name("flashcards")
                     .about("Flashcards")
-                    .arg(arg_file.clone())
+                    .arg(arg_files.clone())
+                    .arg(arg_all.clone())
+                    .arg(arg_weight.clone())
                     .arg(arg_tags.clone())
-                    .arg(arg_phon.clone()))
+                    .arg(arg_phon.clone())
+                    .arg(arg_promptfield.clone())
+                    .arg(arg_answerfield.clone())
+                    .arg(arg_count.clone())
+                    .arg(arg_minutes.clone())
+                    .arg(arg_summary.clone()))
         .subcommand(SubCommand::with_name("pick")
                     .about("Pick and display a random word")
-                    .arg(arg_file.clone())
+                    .arg(arg_files.clone())
+                    .arg(arg_all.clone())
+                    .arg(arg_weight.clone())
                     .arg(arg_tags.clone())
                     .arg(arg_phon.clone())
                     .arg(arg_translations.clone())
@@ -498,14 +2570,85 @@ fn main() {
                     .arg(arg_translations.clone())
                     .arg(arg_examples.clone())
                     .arg(arg_comments.clone()))
+        .subcommand(SubCommand::with_name("restore")
+                    .about("Bring a suspended, buried or known word back into practice, lists all such words if no word is given (give the same fields as in the game, each pair of fields has its own scores)")
+                    .arg(arg_file.clone())
+                    .arg(arg_promptfield.clone())
+                    .arg(arg_answerfield.clone())
+                    .arg(Arg::with_name("word")
+                        .help("The word")
+                        .index(2)))
         .subcommand(SubCommand::with_name("quiz")
                     .about("Simple open quiz")
-                    .arg(arg_file.clone())
+                    .arg(arg_files.clone())
+                    .arg(arg_all.clone())
+                    .arg(arg_weight.clone())
                     .arg(arg_tags.clone())
-                    .arg(arg_phon.clone()))
+                    .arg(arg_phon.clone())
+                    .arg(arg_promptfield.clone())
+                    .arg(arg_answerfield.clone())
+                    .arg(arg_count.clone())
+                    .arg(arg_minutes.clone())
+                    .arg(arg_summary.clone()))
+        .subcommand(SubCommand::with_name("cloze")
+                    .about("Fill in the missing word in the example sentences")
+                    .arg(arg_files.clone())
+                    .arg(arg_all.clone())
+                    .arg(arg_weight.clone())
+                    .arg(arg_tags.clone())
+                    .arg(arg_phon.clone())
+                    .arg(arg_count.clone())
+                    .arg(arg_minutes.clone())
+                    .arg(arg_summary.clone()))
+        .subcommand(SubCommand::with_name("hangman")
+                    .about("Guess the word letter by letter, with the translation as a clue")
+                    .arg(arg_files.clone())
+                    .arg(arg_all.clone())
+                    .arg(arg_weight.clone())
+                    .arg(arg_tags.clone())
+                    .arg(arg_phon.clone())
+                    .arg(Arg::with_name("strictdiacritics")
+                         .help("Letters with diacritics must be guessed exactly (e.g. e does not reveal é)")
+                         .long("strict-diacritics"))
+                    .arg(arg_count.clone())
+                    .arg(arg_minutes.clone())
+                    .arg(arg_summary.clone()))
+        .subcommand(SubCommand::with_name("anagram")
+                    .about("Unscramble the letters of a word, with the translation as a clue")
+                    .arg(arg_files.clone())
+                    .arg(arg_all.clone())
+                    .arg(arg_weight.clone())
+                    .arg(arg_tags.clone())
+                    .arg(arg_phon.clone())
+                    .arg(Arg::with_name("decoys")
+                         .help("Number of decoy letters to mix in (makes it harder)")
+                         .long("decoys")
+                         .takes_value(true)
+                         .default_value("0"))
+                    .arg(arg_count.clone())
+                    .arg(arg_minutes.clone())
+                    .arg(arg_summary.clone()))
+        .subcommand(SubCommand::with_name("speed")
+                    .about("Speed round: answer as many items as possible in a fixed time")
+                    .arg(arg_files.clone())
+                    .arg(arg_all.clone())
+                    .arg(arg_weight.clone())
+                    .arg(arg_tags.clone())
+                    .arg(arg_phon.clone())
+                    .arg(Arg::with_name("seconds")
+                         .help("Duration of the round in seconds")
+                         .long("seconds")
+                         .short("S")
+                         .takes_value(true)
+                         .default_value("60"))
+                    .arg(arg_promptfield.clone())
+                    .arg(arg_answerfield.clone())
+                    .arg(arg_summary.clone()))
         .subcommand(SubCommand::with_name("choicequiz")
                     .about("Simple multiple-choice quiz")
-                    .arg(arg_file.clone())
+                    .arg(arg_files.clone())
+                    .arg(arg_all.clone())
+                    .arg(arg_weight.clone())
                     .arg(arg_tags.clone())
                     .arg(Arg::with_name("multiplechoice")
                          .help("Multiple choice (number of choices)")
@@ -514,10 +2657,154 @@ fn main() {
                          .takes_value(true)
                          .default_value("6")
                     )
-                    .arg(arg_phon.clone()))
-        .subcommand(SubCommand::with_name("matchquiz")
+                    .arg(Arg::with_name("distractors")
+                         .help("Strategy for choosing the wrong options")
+                         .long("distractors")
+                         .takes_value(true)
+                         .possible_values(&["random","tags","length","spelling","transcription","confused","mixed"])
+                         .default_value("mixed")
+                    )
+                    .arg(arg_phon.clone())
+                    .arg(arg_promptfield.clone())
+                    .arg(arg_answerfield.clone())
+                    .arg(arg_count.clone())
+                    .arg(arg_minutes.clone())
+                    .arg(arg_summary.clone()))
+        .subcommand(SubCommand::with_name("exam")
+                    .about("Exam with a fixed number of questions, no feedback until the final report")
+                    .arg(arg_file.clone())
+                    .arg(arg_tags.clone())
+                    .arg(arg_phon.clone())
+                    .arg(Arg::with_name("number")
+                         .help("Number of questions")
+                         .long("number")
+                         .short("n")
+                         .takes_value(true)
+                         .default_value("20")
+                    )
+                    .arg(Arg::with_name("seed")
+                         .help("Random seed, use the same seed to get the same questions again")
+                         .long("seed")
+                         .takes_value(true)
+                    ))
+        .subcommand(SubCommand::with_name("crossword")
+                    .about("Crossword puzzle, to solve in the terminal or to print (with a separate answer key)")
+                    .arg(arg_files.clone())
+                    .arg(arg_all.clone())
+                    .arg(arg_weight.clone())
+                    .arg(arg_tags.clone())
+                    .arg(Arg::with_name("number")
+                         .help("Maximum number of words in the puzzle")
+                         .long("number")
+                         .short("n")
+                         .takes_value(true)
+                         .default_value("12")
+                    )
+                    .arg(Arg::with_name("clues")
+                         .help("Field to use for the clues (an example is shown with the word left out)")
+                         .long("clues")
+                         .takes_value(true)
+                         .default_value("translation")
+                    )
+                    .arg(Arg::with_name("format")
+                         .help("Output format, terminal is an interactive puzzle")
+                         .long("format")
+                         .short("f")
+                         .takes_value(true)
+                         .possible_values(&["terminal", "text", "svg", "html"])
+                         .default_value("terminal")
+                    )
+                    .arg(Arg::with_name("output")
+                         .help("Output file for the printable formats, the answer key is written alongside it (e.g. puzzle.key.svg)")
+                         .long("output")
+                         .short("o")
+                         .takes_value(true)
+                    )
+                    .arg(Arg::with_name("seed")
+                         .help("Random seed for the printable formats, use the same seed to get the same puzzle again")
+                         .long("seed")
+                         .takes_value(true)
+                    )
+                    .arg(arg_count.clone())
+                    .arg(arg_minutes.clone())
+                    .arg(arg_summary.clone()))
+        .subcommand(SubCommand::with_name("wordsearch")
+                    .about("Word search puzzle, with the translations as clues")
                     .arg(arg_file.clone())
                     .arg(arg_tags.clone())
+                    .arg(Arg::with_name("number")
+                         .help("Maximum number of words to hide")
+                         .long("number")
+                         .short("n")
+                         .takes_value(true)
+                         .default_value("10")
+                    )
+                    .arg(Arg::with_name("size")
+                         .help("Size of the grid, either a single number or width x height (e.g. 15x10)")
+                         .long("size")
+                         .takes_value(true)
+                         .default_value("12")
+                    )
+                    .arg(Arg::with_name("directions")
+                         .help("Directions the words may run in: easy (e,s), normal (e,s,se,ne), hard (all), or a comma separated list of e, s, se, ne, w, n, nw, sw")
+                         .long("directions")
+                         .takes_value(true)
+                         .default_value("normal")
+                    )
+                    .arg(Arg::with_name("alphabet")
+                         .help("Letters to fill the grid with (default: the letters used in the list)")
+                         .long("alphabet")
+                         .takes_value(true)
+                    )
+                    .arg(Arg::with_name("clues")
+                         .help("Field to use for the clues (an example is shown with the word left out)")
+                         .long("clues")
+                         .takes_value(true)
+                         .default_value("translation")
+                    )
+                    .arg(Arg::with_name("solution")
+                         .help("Show the answer key as well")
+                         .long("solution")
+                    )
+                    .arg(Arg::with_name("html")
+                         .help("Write a printable HTML version to this file, the answer key is written alongside it (e.g. puzzle.key.html)")
+                         .long("html")
+                         .takes_value(true)
+                    )
+                    .arg(Arg::with_name("seed")
+                         .help("Random seed, use the same seed to get the same puzzle again")
+                         .long("seed")
+                         .takes_value(true)
+                    ))
+        .subcommand(SubCommand::with_name("confusions")
+                    .about("Show which items were confused with eachother most often")
+                    .arg(arg_files.clone())
+                    .arg(arg_all.clone())
+                    .arg(arg_weight.clone()))
+        .subcommand(SubCommand::with_name("confusiondrill")
+                    .about("Practise items that were confused with eachother before")
+                    .arg(arg_files.clone())
+                    .arg(arg_all.clone())
+                    .arg(arg_weight.clone())
+                    .arg(arg_phon.clone())
+                    .arg(arg_count.clone())
+                    .arg(arg_minutes.clone())
+                    .arg(arg_summary.clone()))
+        .subcommand(SubCommand::with_name("tui")
+                    .about("Full-screen terminal interface to browse and practise")
+                    .arg(arg_files.clone())
+                    .arg(arg_all.clone())
+                    .arg(arg_weight.clone())
+                    .arg(arg_tags.clone())
+                    .arg(arg_phon.clone())
+                    .arg(arg_count.clone())
+                    .arg(arg_minutes.clone())
+                    .arg(arg_summary.clone()))
+        .subcommand(SubCommand::with_name("matchquiz")
+                    .arg(arg_files.clone())
+                    .arg(arg_all.clone())
+                    .arg(arg_weight.clone())
+                    .arg(arg_tags.clone())
                     .arg(Arg::with_name("number")
                          .help("Number of pairs to match")
                          .long("number")
@@ -525,8 +2812,73 @@ fn main() {
                          .takes_value(true)
                          .default_value("6")
                     )
-                    .arg(arg_phon.clone()))
-        .get_matches();
+                    .arg(arg_phon.clone())
+                    .arg(arg_promptfield.clone())
+                    .arg(arg_answerfield.clone())
+                    .arg(arg_count.clone())
+                    .arg(arg_minutes.clone())
+                    .arg(arg_summary.clone()))
+        .subcommand(SubCommand::with_name("memory")
+                    .about("Memory game: find the pairs among face-down cards")
+                    .arg(arg_files.clone())
+                    .arg(arg_all.clone())
+                    .arg(arg_weight.clone())
+                    .arg(arg_tags.clone())
+                    .arg(Arg::with_name("number")
+                         .help("Number of pairs on the board")
+                         .long("number")
+                         .short("n")
+                         .takes_value(true)
+                         .default_value("6")
+                    )
+                    .arg(arg_promptfield.clone())
+                    .arg(arg_answerfield.clone())
+                    .arg(arg_count.clone())
+                    .arg(arg_minutes.clone())
+                    .arg(arg_summary.clone()))
+        .subcommand(SubCommand::with_name("resume")
+                    .about("Resume an interrupted match quiz or exam")
+        );
+    let mut argmatches = app.clone().get_matches();
+
+    //resuming a game is like running it again with the same command line, along with the saved state
+    let mut savedgame: Option<SavedGame> = None;
+    let savedgamefile = getsavedgamefile(PathBuf::from(argmatches.value_of("scoredir").expect("Invalid score dir")), argmatches.value_of("accesskey"));
+    match argmatches.subcommand_name().map(|name| name.to_string()).as_deref() {
+        Some("resume") => {
+            match SavedGame::load(savedgamefile.to_str().expect("Invalid session file")) {
+                Ok(game) => {
+                    enterdirectory(&game);
+                    argmatches = app.get_matches_from(game.args.iter());
+                    savedgame = Some(game);
+                },
+                Err(_) => {
+                    eprintln!("There is no interrupted game to resume");
+                    exit(1);
+                }
+            }
+        },
+        Some(name) if name == "exam" || name == "matchquiz" => {
+            //starting the same game afresh, offer to resume the interrupted one instead
+            if let Ok(game) = SavedGame::load(savedgamefile.to_str().expect("Invalid session file")) {
+                let gamematches = app.get_matches_from(game.args.iter());
+                if gamematches.subcommand_name() == Some(name) {
+                    if confirm(&format!("There is an interrupted game ({}), resume it? Otherwise it is discarded.", game.describe()), true) {
+                        enterdirectory(&game);
+                        argmatches = gamematches;
+                        savedgame = Some(game);
+                    } else {
+                        fs::remove_file(&savedgamefile).expect("Unable to remove session file");
+                    }
+                }
+            }
+        },
+        _ => {}
+    }
+    let args: Vec<String> = match savedgame {
+        Some(ref game) => game.args.clone(),
+        None => std::env::args().collect()
+    };
 
     let debug = argmatches.is_present("debug");
 
@@ -534,6 +2886,7 @@ fn main() {
     fs::create_dir_all(&datadir).expect("Unable to create data directory");
     let scoredir = PathBuf::from(argmatches.value_of("scoredir").expect("Invalid score dir"));
     fs::create_dir_all(&scoredir).expect("Unable to create score directory");
+    let savedgamefile = getsavedgamefile(scoredir.clone(), argmatches.value_of("accesskey"));
 
     if debug {
         eprintln!(" (data directory is {})", &datadir.to_str().unwrap());
@@ -552,30 +2905,119 @@ fn main() {
                 println!("{}", file.to_str().unwrap());
             }
         },
+        Some("review") => {
+            let submatches = argmatches.subcommand_matches("review").unwrap();
+            let accesskey = argmatches.value_of("accesskey");
+            let statsfile = getstatsfile(scoredir.clone(), accesskey);
+            let mut stats: ReviewStats = match statsfile.exists() {
+                true => ReviewStats::load(statsfile.to_str().expect("Invalid stats file")).expect("Unable to load review statistics"),
+                false => ReviewStats::default()
+            };
+            if submatches.is_present("stats") {
+                reviewstats(&stats);
+            } else {
+                let mut lists: Vec<ReviewList> = Vec::new();
+                for datafile in getdatafiles(&datadir) {
+                    //anything that is not a vocabulary list (e.g. score files) is skipped
+                    if let Ok(data) = VocaList::parse(datafile.to_str().unwrap()) {
+                        let scorefile = getscorefile(datafile.to_str().unwrap(), scoredir.clone(), accesskey, None);
+                        let scoredata = match scorefile.exists() {
+                            true => VocaScore::load(scorefile.to_str().expect("Invalid score file")).expect("Unable to load score file"),
+                            false => VocaScore::default()
+                        };
+                        if debug {
+                            eprintln!(" (reviewing {}, score file is {})", datafile.to_str().unwrap(), scorefile.to_str().unwrap());
+                        }
+                        let template = gettemplate(&data);
+                        lists.push(ReviewList { data, scoredata, scorefile, template });
+                    }
+                }
+                let maxnew: usize = submatches.value_of("new").unwrap().parse().expect("Not a valid number for --new");
+                let maxreviews: usize = submatches.value_of("max").unwrap().parse().expect("Not a valid number for --max");
+                let maxduration: Option<u64> = submatches.value_of("minutes").map(|minutes| minutes.parse::<u64>().expect("Not a valid number for --minutes") * 60);
+                let mut session = Session::new(None, maxduration);
+                review(&mut lists, &mut stats, maxnew, maxreviews, submatches.is_present("phon"), &mut session);
+                for list in lists.iter() {
+                    list.scoredata.save(list.scorefile.to_str().expect("Invalid score file")).expect("Unable to save");
+                }
+                stats.save(statsfile.to_str().expect("Invalid stats file")).expect("Unable to save review statistics");
+                session.finish();
+                println!("SESSION SUMMARY");
+                println!("---------------------------------------------------------------------------------");
+                session.print();
+                println!("Streak:          {} days", stats.currentstreak());
+                if let Some(summaryfile) = submatches.value_of("summary") {
+                    session.save(summaryfile).expect("Unable to save session summary");
+                }
+            }
+        },
         _ => { // all other subcommands that take a file parameter
             let submatches = argmatches.subcommand_matches(argmatches.subcommand_name().unwrap()).unwrap();
-            let filename = submatches.value_of("file").expect("Expected filename");
-            let datafile: Option<String> = if Path::new(filename).exists() {
-                eprintln!("Loading {}", filename);
-                Some(filename.to_string())
+            let filenames: Vec<String> = if submatches.is_present("all") {
+                //anything that is not a vocabulary list (e.g. score files) is skipped
+                getdatafiles(&datadir).into_iter()
+                    .filter(|datafile| VocaList::parse(datafile.to_str().unwrap()).is_ok())
+                    .map(|datafile| datafile.to_str().unwrap().to_string())
+                    .collect()
             } else {
-                getdatafile(filename, datadir).map(|f| f.to_str().unwrap().to_string()) //Option<PathBuf> to Option<String>, this looks a bit convoluted to me, revisit later
+                submatches.values_of("file").expect("Expected filename").map(|filename| filename.to_string()).collect()
             };
-                //This would iterate over all available files but is unnecessarily expensive
-                //compared to the above:
-                /*if let Some(founditem) = dataindex.iter().find(|e| e.file_stem().unwrap() == filename) {
-                    datafile = founditem.to_str();
-                }*/
-            if datafile == None {
+            let mut datafiles: Vec<String> = Vec::new();
+            for filename in filenames.iter() {
+                let datafile: Option<String> = if Path::new(filename).exists() {
+                    eprintln!("Loading {}", filename);
+                    Some(filename.to_string())
+                } else {
+                    getdatafile(filename, datadir.clone()).map(|f| f.to_str().unwrap().to_string()) //Option<PathBuf> to Option<String>, this looks a bit convoluted to me, revisit later
+                };
+                    //This would iterate over all available files but is unnecessarily expensive
+                    //compared to the above:
+                    /*if let Some(founditem) = dataindex.iter().find(|e| e.file_stem().unwrap() == filename) {
+                        datafile = founditem.to_str();
+                    }*/
+                match datafile {
+                    None => {
+                        eprintln!("Data file not found: {}", filename);
+                        std::process::exit(1);
+                    },
+                    Some(datafile) => {
+                        if debug {
+                            eprintln!(" (data file is {})", datafile);
+                        }
+                        datafiles.push(datafile);
+                    }
+                }
+            }
+            if datafiles.is_empty() {
                 eprintln!("Data file not found");
                 std::process::exit(1);
-            } else if debug {
-                eprintln!(" (data file is {})", datafile.as_ref().unwrap());
             }
+            let mut weights: HashMap<String, f64> = HashMap::new();
+            for weight in submatches.values_of("weight").into_iter().flatten() {
+                match weight.find('=').map(|pos| (&weight[..pos], weight[pos+1..].parse::<f64>())) {
+                    Some((name, Ok(value))) if value > 0.0 => {
+                        weights.insert(name.strip_suffix(".json").unwrap_or(name).to_string(), value);
+                    },
+                    _ => {
+                        eprintln!("Invalid weight: {} (expected list=weight, with a positive weight)", weight);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            let datafile: Option<String> = datafiles.first().cloned();
             let filebase = PathBuf::from(datafile.clone().unwrap().as_str());
-            let scorefile = getscorefile(filebase.to_str().unwrap(), scoredir, submatches.value_of("accesskey"));
+            let fields = match FieldPair::new(submatches.value_of("promptfield").unwrap_or("word"), submatches.value_of("answerfield").unwrap_or("translation")) {
+                Ok(fields) => fields,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            };
+            let scorefiles: Vec<PathBuf> = datafiles.iter().map(|datafile| getscorefile(datafile, scoredir.clone(), submatches.value_of("accesskey"), Some(&fields))).collect();
             if debug {
-                eprintln!(" (score file is {})", scorefile.to_str().unwrap());
+                for scorefile in scorefiles.iter() {
+                    eprintln!(" (score file is {})", scorefile.to_str().unwrap());
+                }
             }
             let filtertags: Option<Vec<&str>> = submatches.value_of("tags").map(|tagstring: &str| {
                 tagstring.split_terminator(',').collect()
@@ -591,55 +3033,312 @@ fn main() {
                 let tags: Option<Vec<&str>> = submatches.value_of("tags").map(|tagstring: &str| {
                     tagstring.split_terminator(',').collect()
                 });
-                data.append(word,  translation, phon, example, comment, tags.as_ref());
+                let mut extra: BTreeMap<String, String> = BTreeMap::new();
+                for field in submatches.values_of("field").into_iter().flatten() {
+                    match field.find('=') {
+                        Some(pos) if isfieldname(&field[..pos]) && !FIELDS.contains(&&field[..pos]) => {
+                            extra.insert(field[..pos].to_string(), field[pos+1..].to_string());
+                        },
+                        _ => {
+                            eprintln!("Invalid custom field: {} (expected key=value, the key may not be a standard field)", field);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                data.append(word,  translation, phon, example, comment, tags.as_ref(), extra);
                 data.save(datafile.as_ref().unwrap()).expect("Unable to save");
             } else {
                 //open read only
-                match VocaList::parse(datafile.as_ref().unwrap()) {
+                match loaddata(&datafiles, &weights) {
                     Ok(data) => {
                         //see what subcommand to perform
                         match argmatches.subcommand_name() {
                             Some("show") => {
-                                data.show(submatches.is_present("translations"), submatches.is_present("phon"), filtertags.as_ref(), submatches.is_present("showtags"), submatches.is_present("examples"), submatches.is_present("comments"));
+                                data.show(submatches.is_present("translations"), submatches.is_present("phon"), filtertags.as_ref(), submatches.is_present("showtags"), submatches.is_present("examples"), submatches.is_present("comments"), submatches.is_present("extra"));
                             },
                             Some("csv") => {
                                 data.csv(filtertags.as_ref()).expect("Error during CSV serialisation");
                             },
-                            Some("pick") | Some("find") | Some("quiz") | Some("choicequiz") | Some("matchquiz") | Some("flashcards") => {
-                                let mut optscoredata: Option<VocaScore> = match scorefile.exists() {
-                                    true => VocaScore::load(scorefile.to_str().expect("Invalid score file")).ok(),
-                                    false => Some(VocaScore { ..Default::default() } ),
+                            Some("exam") => {
+                                let count: usize = submatches.value_of("number").unwrap().parse().expect("Not a valid number for --number");
+                                //a resumed exam continues with the same questions
+                                let resumed: Option<ExamRecord> = savedgame.as_mut().and_then(|game| game.exam.take());
+                                let seed: u64 = match (&resumed, submatches.value_of("seed")) {
+                                    (Some(record), _) => record.seed,
+                                    (None, Some(seed)) => seed.parse().expect("Not a valid number for --seed"),
+                                    (None, None) => rand::random::<u32>() as u64
+                                };
+                                let items = data.sample(count, filtertags.as_ref(), seed);
+                                if items.is_empty() {
+                                    eprintln!("No items to ask");
+                                    success = false;
+                                } else {
+                                    eprintln!("(seed is {}, use --seed to repeat this exam)", seed);
+                                    let name = filebase.file_stem().unwrap().to_str().unwrap();
+                                    let mut record = resumed.unwrap_or_else(|| ExamRecord::new(name, seed, items.len()));
+                                    if exam(&items, &mut record, submatches.is_present("phon")) {
+                                        let examfile = getexamfile(filebase.to_str().unwrap(), scoredir, submatches.value_of("accesskey"), record.started);
+                                        record.save(examfile.to_str().expect("Invalid exam file")).expect("Unable to save exam");
+                                        eprintln!("(exam saved to {})", examfile.to_str().unwrap());
+                                        if savedgame.is_some() {
+                                            fs::remove_file(&savedgamefile).expect("Unable to remove session file");
+                                        }
+                                    } else {
+                                        let game = SavedGame { args: args.clone(), exam: Some(record), ..Default::default() };
+                                        if savegame(game, &savedgamefile, savedgame.is_some()) {
+                                            eprintln!("(exam paused, use vj resume to continue)");
+                                        } else {
+                                            eprintln!("(exam not saved)");
+                                        }
+                                    }
+                                }
+                            },
+                            Some("crossword") if submatches.value_of("format") != Some("terminal") => {
+                                let count: usize = submatches.value_of("number").unwrap().parse().expect("Not a valid number for --number");
+                                let cluefield = submatches.value_of("clues").unwrap();
+                                let seed: u64 = match submatches.value_of("seed") {
+                                    Some(seed) => seed.parse().expect("Not a valid number for --seed"),
+                                    None => rand::random::<u32>() as u64
+                                };
+                                let items: Vec<&VocaItem> = data.sample(data.items.len(), filtertags.as_ref(), seed).into_iter().filter(|item| getclue(item, cluefield).is_some()).collect();
+                                let clues: Vec<String> = items.iter().map(|item| getclue(item, cluefield).unwrap_or_default()).collect();
+                                let puzzle = Crossword::generate(&items, &clues, count);
+                                let format = submatches.value_of("format").unwrap();
+                                if puzzle.entries.len() < 2 {
+                                    eprintln!("Unable to build a crossword from these items");
+                                    success = false;
+                                } else if let Some(output) = submatches.value_of("output") {
+                                    let title = format!("Crossword: {}", filebase.file_stem().unwrap().to_str().unwrap());
+                                    let (puzzletext, keytext) = match format {
+                                        "svg" => (puzzle.tosvg(false), puzzle.tosvg(true)),
+                                        "html" => (puzzle.tohtml(&title, false), puzzle.tohtml(&title, true)),
+                                        _ => (puzzle.totext(&vec![false; puzzle.entries.len()]) + "\n" + &puzzle.cluestext(), puzzle.totext(&vec![true; puzzle.entries.len()]) + "\n" + &puzzle.answerstext())
+                                    };
+                                    let output = PathBuf::from(output);
+                                    let keyfile = getkeyfile(&output);
+                                    fs::write(&output, puzzletext).expect("Unable to write puzzle");
+                                    fs::write(&keyfile, keytext).expect("Unable to write answer key");
+                                    eprintln!("(seed is {}, puzzle written to {}, answer key to {})", seed, output.to_str().unwrap(), keyfile.to_str().unwrap());
+                                } else if format == "text" {
+                                    eprintln!("(seed is {}, use --seed to get this puzzle again)", seed);
+                                    print!("{}", puzzle.totext(&vec![false; puzzle.entries.len()]));
+                                    println!();
+                                    print!("{}", puzzle.cluestext());
+                                    println!("ANSWER KEY");
+                                    println!("---------------------------------------------------------------------------------");
+                                    print!("{}", puzzle.totext(&vec![true; puzzle.entries.len()]));
+                                    println!();
+                                    print!("{}", puzzle.answerstext());
+                                } else {
+                                    eprintln!("An output file (--output) is required for the {} format", format);
+                                    success = false;
+                                }
+                            },
+                            Some("wordsearch") => {
+                                let count: usize = submatches.value_of("number").unwrap().parse().expect("Not a valid number for --number");
+                                let cluefield = submatches.value_of("clues").unwrap();
+                                let size = submatches.value_of("size").unwrap();
+                                let (width, height): (usize, usize) = match size.find('x') {
+                                    Some(pos) => (size[..pos].parse().expect("Not a valid width for --size"), size[pos+1..].parse().expect("Not a valid height for --size")),
+                                    None => { let size = size.parse().expect("Not a valid number for --size"); (size, size) }
+                                };
+                                if width == 0 || height == 0 {
+                                    eprintln!("The width and height of --size must be at least 1");
+                                    exit(1);
+                                }
+                                let directions = match WordSearch::parsedirections(submatches.value_of("directions").unwrap()) {
+                                    Ok(directions) => directions,
+                                    Err(err) => {
+                                        eprintln!("{}", err);
+                                        exit(1);
+                                    }
+                                };
+                                let alphabet: Vec<char> = match submatches.value_of("alphabet") {
+                                    Some(alphabet) => Crossword::normalise(alphabet).chars().collect(),
+                                    None => data.alphabet()
+                                };
+                                let seed: u64 = match submatches.value_of("seed") {
+                                    Some(seed) => seed.parse().expect("Not a valid number for --seed"),
+                                    None => rand::random::<u32>() as u64
+                                };
+                                let items: Vec<&VocaItem> = data.sample(data.items.len(), filtertags.as_ref(), seed).into_iter().filter(|item| getclue(item, cluefield).is_some()).collect();
+                                let clues: Vec<String> = items.iter().map(|item| getclue(item, cluefield).unwrap_or_default()).collect();
+                                let puzzle = WordSearch::generate(&items, &clues, count, width, height, &directions, &alphabet, seed);
+                                if puzzle.entries.is_empty() {
+                                    eprintln!("Unable to hide any words in a grid of this size");
+                                    success = false;
+                                } else {
+                                    eprintln!("(seed is {}, use --seed to get this puzzle again)", seed);
+                                    print!("{}", puzzle.totext(false));
+                                    println!();
+                                    println!("FIND THE WORDS FOR:");
+                                    for entry in puzzle.entries.iter() {
+                                        println!("  {}", entry.clue);
+                                    }
+                                    if submatches.is_present("solution") {
+                                        println!();
+                                        println!("ANSWER KEY");
+                                        println!("---------------------------------------------------------------------------------");
+                                        print!("{}", puzzle.totext(true));
+                                        println!();
+                                        for entry in puzzle.entries.iter() {
+                                            println!("  {}: {}", entry.clue, entry.answer);
+                                        }
+                                    }
+                                    if let Some(output) = submatches.value_of("html") {
+                                        let title = format!("Word search: {}", filebase.file_stem().unwrap().to_str().unwrap());
+                                        let output = PathBuf::from(output);
+                                        let keyfile = getkeyfile(&output);
+                                        fs::write(&output, puzzle.tohtml(&title, false)).expect("Unable to write puzzle");
+                                        fs::write(&keyfile, puzzle.tohtml(&title, true)).expect("Unable to write answer key");
+                                        eprintln!("(puzzle written to {}, answer key to {})", output.to_str().unwrap(), keyfile.to_str().unwrap());
+                                    }
+                                }
+                            },
+                            Some("pick") | Some("find") | Some("quiz") | Some("cloze") | Some("hangman") | Some("anagram") | Some("speed") | Some("learn") | Some("choicequiz") | Some("matchquiz") | Some("memory") | Some("crossword") | Some("flashcards") | Some("tui") | Some("confusions") | Some("confusiondrill") | Some("restore") => {
+                                let mut optscoredata: Option<VocaScore> = match (datafiles.len(), scorefiles[0].exists()) {
+                                    (1, true) => VocaScore::load(scorefiles[0].to_str().expect("Invalid score file")).ok(),
+                                    _ => Some(VocaScore { ..Default::default() } ),
                                 };
+                                for (index, scorefile) in scorefiles.iter().enumerate() {
+                                    //the scores of several lists are merged for the session
+                                    if datafiles.len() > 1 && scorefile.exists() {
+                                        optscoredata = match (optscoredata, VocaScore::load(scorefile.to_str().expect("Invalid score file"))) {
+                                            (Some(mut scoredata), Ok(listscoredata)) => {
+                                                scoredata.merge(listscoredata, &data, index);
+                                                Some(scoredata)
+                                            },
+                                            _ => None
+                                        };
+                                    }
+                                }
+                                let maxcount: Option<u32> = submatches.value_of("count").map(|count| count.parse().expect("Not a valid number for --count"));
+                                let maxduration: Option<u64> = submatches.value_of("minutes").map(|minutes| minutes.parse::<u64>().expect("Not a valid number for --minutes") * 60);
+                                let mut session = Session::new(maxcount, maxduration);
+                                if let Some(ref game) = savedgame {
+                                    session.resume(&game.session);
+                                }
+                                let template = gettemplate(&data);
+                                if matches!(argmatches.subcommand_name(), Some("quiz") | Some("speed") | Some("learn") | Some("choicequiz") | Some("matchquiz") | Some("memory") | Some("flashcards")) && !data.items.iter().any(|item| item.filter(filtertags.as_ref()) && fields.applies(item)) {
+                                    eprintln!("No items have both a {} and a {}", fields.prompt, fields.answer);
+                                    exit(1);
+                                }
                                 match argmatches.subcommand_name() {
                                     Some("pick") => {
-                                        pick(&data, optscoredata.as_mut() , submatches.is_present("phon"), submatches.is_present("translations"), submatches.is_present("examples"), filtertags.as_ref());
+                                        pick(&data, optscoredata.as_mut() , template.as_ref(), submatches.is_present("phon"), submatches.is_present("translations"), submatches.is_present("examples"), filtertags.as_ref());
                                     },
                                     Some("find") => {
                                         let word = submatches.value_of("word").expect("No word specified");
-                                        find(&data, &word, optscoredata.as_mut() , submatches.is_present("phon"), submatches.is_present("translations"), submatches.is_present("examples"));
+                                        find(&data, word, optscoredata.as_mut() , submatches.is_present("phon"), submatches.is_present("translations"), submatches.is_present("examples"));
                                     },
                                     Some("choicequiz") => {
                                         if let Some(choicecount) = submatches.value_of("multiplechoice") {
                                             let choicecount: u32 = choicecount.parse().expect("Not a valid number for --multiplechoice");
-                                            multiquiz(&data, optscoredata.as_mut(), choicecount, submatches.is_present("phon"), filtertags.as_ref());
+                                            if choicecount < 2 {
+                                                eprintln!("Expected at least two choices");
+                                                exit(1);
+                                            }
+                                            let strategy = DistractorStrategy::parse(submatches.value_of("distractors").unwrap()).expect("Invalid distractor strategy");
+                                            multiquiz(&data, optscoredata.as_mut(), choicecount, &fields, strategy, submatches.is_present("phon"), filtertags.as_ref(), &mut session);
                                         }
                                     },
                                     Some("matchquiz") => {
                                         if let Some(matchcount) = submatches.value_of("number") {
-                                            let matchcount: u8 = matchcount.parse().expect("Not a valid number for --number");
-                                            matchquiz(&data, optscoredata.as_mut(), matchcount, submatches.is_present("phon"), filtertags.as_ref());
+                                            let matchcount: usize = matchcount.parse().expect("Not a valid number for --number");
+                                            match matchquiz(&data, optscoredata.as_mut(), matchcount, &fields, submatches.is_present("phon"), filtertags.as_ref(), &mut session, savedgame.as_ref()) {
+                                                Some(mut game) => {
+                                                    session.finish();
+                                                    game.args = args.clone();
+                                                    game.session = session.clone();
+                                                    if savegame(game, &savedgamefile, savedgame.is_some()) {
+                                                        eprintln!("(round saved, use vj resume to continue)");
+                                                    } else {
+                                                        eprintln!("(round not saved)");
+                                                    }
+                                                },
+                                                None if savedgame.is_some() => {
+                                                    fs::remove_file(&savedgamefile).expect("Unable to remove session file");
+                                                },
+                                                None => {}
+                                            }
                                         }
                                     },
+                                    Some("memory") => {
+                                        let paircount: usize = submatches.value_of("number").unwrap().parse().expect("Not a valid number for --number");
+                                        memory(&data, optscoredata.as_mut(), paircount, &fields, filtertags.as_ref(), &mut session);
+                                    },
+                                    Some("crossword") => {
+                                        let count: usize = submatches.value_of("number").unwrap().parse().expect("Not a valid number for --number");
+                                        crossword(&data, optscoredata.as_mut(), count, submatches.value_of("clues").unwrap(), filtertags.as_ref(), &mut session);
+                                    },
                                     Some("quiz") => {
-                                        quiz(&data, optscoredata.as_mut() , submatches.is_present("phon"), filtertags.as_ref());
+                                        quiz(&data, optscoredata.as_mut() , &fields, submatches.is_present("phon"), filtertags.as_ref(), &mut session);
+                                    },
+                                    Some("cloze") => {
+                                        clozequiz(&data, optscoredata.as_mut() , submatches.is_present("phon"), filtertags.as_ref(), &mut session);
+                                    },
+                                    Some("hangman") => {
+                                        hangman(&data, optscoredata.as_mut() , submatches.is_present("phon"), submatches.is_present("strictdiacritics"), filtertags.as_ref(), &mut session);
+                                    },
+                                    Some("anagram") => {
+                                        let decoys: usize = submatches.value_of("decoys").unwrap().parse().expect("Not a valid number for --decoys");
+                                        anagram(&data, optscoredata.as_mut() , decoys, submatches.is_present("phon"), filtertags.as_ref(), &mut session);
+                                    },
+                                    Some("speed") => {
+                                        let seconds: u64 = submatches.value_of("seconds").unwrap().parse().expect("Not a valid number for --seconds");
+                                        speedround(&data, optscoredata.as_mut(), seconds, &fields, submatches.is_present("phon"), filtertags.as_ref(), &mut session);
+                                    },
+                                    Some("learn") => {
+                                        let newcount: usize = submatches.value_of("new").unwrap().parse().expect("Not a valid number for --new");
+                                        let dailylimit: usize = submatches.value_of("daily").unwrap().parse().expect("Not a valid number for --daily");
+                                        learn(&data, optscoredata.as_mut(), newcount, dailylimit, &fields, template.as_ref(), submatches.is_present("phon"), filtertags.as_ref(), &mut session);
                                     },
                                     Some("flashcards") => {
-                                        flashcards(&data, optscoredata.as_mut() , submatches.is_present("phon"), filtertags.as_ref());
+                                        flashcards(&data, optscoredata.as_mut() , &fields, template.as_ref(), submatches.is_present("phon"), filtertags.as_ref(), &mut session);
+                                    },
+                                    Some("confusions") => {
+                                        if let Some(ref scoredata) = optscoredata {
+                                            confusionreport(&data, scoredata);
+                                        }
+                                    },
+                                    Some("restore") => {
+                                        if let Some(ref mut scoredata) = optscoredata {
+                                            success = restore(&data, scoredata, submatches.value_of("word"));
+                                        }
+                                    },
+                                    Some("confusiondrill") => {
+                                        if let Some(ref mut scoredata) = optscoredata {
+                                            confusiondrill(&data, scoredata, submatches.is_present("phon"), &mut session);
+                                        }
+                                    },
+                                    Some("tui") => {
+                                        tui(&data, optscoredata.as_mut() , submatches.is_present("phon"), filtertags.as_ref(), &mut session);
                                     },
                                     _ => {}
                                 }
                                 if let Some(ref scoredata) = optscoredata {
-                                    scoredata.save(scorefile.to_str().expect("Invalid score file")).expect("Unable to save");
+                                    if datafiles.len() == 1 {
+                                        scoredata.save(scorefiles[0].to_str().expect("Invalid score file")).expect("Unable to save");
+                                    } else {
+                                        //every list keeps its own score file
+                                        for (index, scorefile) in scorefiles.iter().enumerate() {
+                                            let mut listscoredata = match scorefile.exists() {
+                                                true => VocaScore::load(scorefile.to_str().expect("Invalid score file")).expect("Unable to load score file"),
+                                                false => VocaScore::default()
+                                            };
+                                            listscoredata.update(scoredata, &data, index);
+                                            listscoredata.save(scorefile.to_str().expect("Invalid score file")).expect("Unable to save");
+                                        }
+                                    }
+                                }
+                                if !matches!(argmatches.subcommand_name(), Some("pick") | Some("find") | Some("confusions") | Some("restore")) {
+                                    session.finish();
+                                    println!("SESSION SUMMARY");
+                                    println!("---------------------------------------------------------------------------------");
+                                    session.print();
+                                    if let Some(summaryfile) = submatches.value_of("summary") {
+                                        session.save(summaryfile).expect("Unable to save session summary");
+                                    }
                                 }
                             },
                             _ => {
@@ -2,7 +2,6 @@ extern crate clap;
 extern crate reqwest;
 extern crate serde;
 
-use std::error::Error;
 use std::process::exit;
 use clap::{App, Arg, SubCommand};
 use vocajeux::*;
@@ -171,7 +170,6 @@ fn main() {
                     .arg(arg_phon.clone()))
         .get_matches();
 
-    let debug = argmatches.is_present("debug");
     let url = argmatches.value_of("url").expect("no url specified");
     match argmatches.subcommand_name() {
         None => {
@@ -209,7 +207,7 @@ fn main() {
                 "show" => {
                     match show(url, argmatches.value_of("dataset").expect("No dataset specified")) {
                         Ok(vocalist) => {
-                            vocalist.show(submatches.is_present("translation"), submatches.is_present("phon"), None, false, submatches.is_present("example"), false);
+                            vocalist.show(submatches.is_present("translation"), submatches.is_present("phon"), None, false, submatches.is_present("example"), false, false);
                         }
                         Err(err) => println!("ERROR: {}", err),
                     }
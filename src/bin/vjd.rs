@@ -36,19 +36,15 @@ struct NotFoundError;
 
 impl fmt::Display for NotFoundError {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt.write_str(self.description())
+        fmt.write_str("not found")
     }
 }
 
-impl Error for NotFoundError {
-    fn description(&self) -> &str {
-        "not found"
-    }
-}
+impl Error for NotFoundError {}
 
 
 ///Adds a vocabulary list to the loaded data
-fn addvocalist<'a>(state: &'a AppState, dataset: &'a str) -> Result<(), Box<(dyn Error + 'static)> > {
+fn addvocalist<'a>(state: &'a AppState, dataset: &'a str) -> Result<(), Box<dyn Error + 'static> > {
     let mut vocalists = state.data.write().expect("RwLock poisoned");
     if !vocalists.contains_key(dataset) {
         let vocalist = loadvocalist(state, dataset)?;
@@ -61,7 +57,7 @@ fn addvocalist<'a>(state: &'a AppState, dataset: &'a str) -> Result<(), Box<(dyn
 }
 
 ///Adds a vocabulary score to the loaded data
-fn addvocascore<'a>(state: &'a AppState, dataset: &'a str, sessionkey: &'a str) -> Result<(), Box<(dyn Error + 'static)> > {
+fn addvocascore<'a>(state: &'a AppState, dataset: &'a str, sessionkey: &'a str) -> Result<(), Box<dyn Error + 'static> > {
     let mut scores = state.scores.lock().expect("Unable to lock");
     let scorekey = (dataset.to_string(), sessionkey.to_string());
     if !scores.contains_key(&scorekey) {
@@ -87,7 +83,7 @@ fn loadvocalist(state: &AppState, dataset: &str) -> Result<VocaList, Box<dyn Err
 ///Loads and returns a vocabulary scoremap
 fn loadvocascore(state: &AppState, dataset: &str, sessionkey: &str) -> Result<VocaScore,Box<dyn Error> > {
     let scoredir = &*state.scoredir; //deref arc and borrow
-    let scorefile = getscorefile(dataset, PathBuf::from(scoredir), Some(sessionkey));
+    let scorefile = getscorefile(dataset, PathBuf::from(scoredir), Some(sessionkey), None);
     if scorefile.exists() {
        VocaScore::load(scorefile.to_str().unwrap())
     } else {
@@ -107,7 +103,7 @@ fn cleanup(state: &AppState) {
             scores_expire.push(scorekey.clone());
             let (dataset, sessionkey) = scorekey;
             if let Some(vocascore) = scores.get_mut(scorekey) {
-                let scorefile = getscorefile(dataset, PathBuf::from(scoredir), Some(sessionkey));
+                let scorefile = getscorefile(dataset, PathBuf::from(scoredir), Some(sessionkey), None);
                 if let Err(err) = vocascore.save(scorefile.to_str().unwrap()) {
                     eprintln!("Error during serialisation of {}: {}",scorefile.to_str().unwrap(), err);
                 }
@@ -145,7 +141,7 @@ fn index(_req: HttpRequest<AppState>) -> impl Responder {
 /// Show the entire vocabulary list
 fn show(req: HttpRequest<AppState>) -> impl Responder {
     if let Some(dataset) = req.match_info().get_decoded("dataset") {
-        match loadvocalist(&req.state(), &dataset) { //loads directly from file rather than using the one in the state
+        match loadvocalist(req.state(), &dataset) { //loads directly from file rather than using the one in the state
             Ok(data) => {
                 Json(data).respond_to(&req).unwrap_or(HttpResponse::NotFound().finish())
             },
@@ -164,18 +160,18 @@ fn show(req: HttpRequest<AppState>) -> impl Responder {
 fn handle(req: HttpRequest<AppState>, handler: impl FnOnce(&HttpRequest<AppState>, &VocaList,Option<&mut VocaScore>, bool) -> HttpResponse) -> impl Responder {
     let state = &req.state();
     //parse query parameter:
-    let seen = match req.query().get("seen").map(|x| { x.as_str() }) {
-        Some("no") | Some("0") | Some("false") => true,
-        _ => false
-    };
+    let seen = matches!(req.query().get("seen").map(|x| { x.as_str() }), Some("no") | Some("0") | Some("false"));
 
     if let Some(dataset) = req.match_info().get_decoded("dataset"){
         match addvocalist(state, &dataset) {
             Ok(_) => {
-                let mut scores = state.scores.lock().expect("Unable to get score lock");
                 let sessionkey = req.match_info().get_decoded("session");
+                if let Some(ref sessionkey) = sessionkey {
+                    //must happen before we lock the scores ourselves
+                    addvocascore(state,&dataset,sessionkey).ok();
+                }
+                let mut scores = state.scores.lock().expect("Unable to get score lock");
                 let vocascore = if let Some(sessionkey) = sessionkey {
-                    addvocascore(state,&dataset,&sessionkey).ok();
                     let scorekey = (dataset.to_string(), sessionkey.to_string());
                     scores.get_mut(&scorekey)
                 } else {
@@ -206,8 +202,11 @@ fn handle(req: HttpRequest<AppState>, handler: impl FnOnce(&HttpRequest<AppState
 ///Get a random item from a vocabulary list
 fn pick(req: HttpRequest<AppState>) -> impl Responder {
     handle(req, |req,vocalist, vocascore, seen| {
-        let vocaitem = vocalist.pick(vocascore,None, seen);
-        Json(vocaitem).respond_to(&req).unwrap_or(HttpResponse::NotFound().finish())
+        if let Some(vocaitem) = vocalist.pick(vocascore,None, seen) {
+            Json(vocaitem).respond_to(req).unwrap_or(HttpResponse::NotFound().finish())
+        } else {
+            HttpResponse::NotFound().body("No items to pick from")
+        }
     })
 }
 
@@ -216,7 +215,7 @@ fn find(req: HttpRequest<AppState>) -> impl Responder {
     handle(req, |req,vocalist, vocascore, seen| {
         if let Some(word) = req.match_info().get("word") {
             if let Some(vocaitem) = vocalist.find(word, vocascore, seen) {
-                Json(vocaitem).respond_to(&req).unwrap_or(HttpResponse::NotFound().finish())
+                Json(vocaitem).respond_to(req).unwrap_or(HttpResponse::NotFound().finish())
             } else {
                 HttpResponse::NotFound().body("Word not found")
             }
@@ -226,20 +225,25 @@ fn find(req: HttpRequest<AppState>) -> impl Responder {
     })
 }
 
-///Mark an item as correct
+///Mark an item as correct or incorrect, the optional 'time' parameter gives the response time in
+///milliseconds
 fn score(req: HttpRequest<AppState>) -> impl Responder {
     handle(req, |req,vocalist, vocascore, _| {
         if let Some(vocascore) = vocascore {
             if let Some(word) = req.match_info().get("word") {
                 if let Some(vocaitem) = vocalist.find(word, Some(vocascore), true) {
-                    let correct: bool;
-                    match req.query().get("correct").map(|x| { x.as_str() }) {
-                        Some("yes") | Some("1") | Some("true") => { correct = true; },
-                        Some("no") | Some("0") | Some("false") => { correct = false; },
+                    let correct: bool = match req.query().get("correct").map(|x| { x.as_str() }) {
+                        Some("yes") | Some("1") | Some("true") => true,
+                        Some("no") | Some("0") | Some("false") => false,
                         Some(_) => { return HttpResponse::NotFound().body("Expected parameter 'correct' has invalid value"); }
                         None => { return HttpResponse::NotFound().body("Expected parameter 'correct' not found"); }
                     };
-                    vocascore.addscore(vocaitem, correct);
+                    //optionally, the time it took to answer (in milliseconds)
+                    match req.query().get("time").map(|x| x.parse::<u64>()) {
+                        Some(Ok(millis)) => vocascore.addtimedscore(vocaitem, correct, millis),
+                        Some(Err(_)) => { return HttpResponse::NotFound().body("Parameter 'time' has invalid value"); },
+                        None => vocascore.addscore(vocaitem, correct)
+                    }
                     HttpResponse::Ok()
                         .header(http::header::CONTENT_TYPE, http::header::ContentType::json())
                         .body("{}") //empty json response
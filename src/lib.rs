@@ -7,17 +7,20 @@ extern crate regex;
 extern crate md5;
 extern crate dirs;
 extern crate csv;
+extern crate unicode_normalization;
 
 use std::fs;
 use std::error::Error;
 use std::fmt;
 use std::io;
 use std::iter::Iterator;
-use std::collections::HashMap;
+use std::collections::{BTreeMap,HashMap};
 use std::time::{SystemTime, UNIX_EPOCH};
-use md5::{compute,Digest};
 use std::path::{Path,PathBuf};
-use std::iter::FromIterator;
+use rand::{Rng,SeedableRng,StdRng};
+use regex::Regex;
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
 
 /// Vocabulary Item data structure
 #[derive(Serialize, Deserialize)]
@@ -33,23 +36,252 @@ pub struct VocaItem {
     #[serde(default)]
     pub comment: String,
     #[serde(default)]
-    pub tags: Vec<String>
+    pub tags: Vec<String>,
+    /// Custom named fields (e.g. kanji reading, classifier, aspect partner)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>
 }
 
 /// Vocabulary List data structure
 #[derive(Serialize, Deserialize)]
 pub struct VocaList {
-    pub items: Vec<VocaItem>
+    pub items: Vec<VocaItem>,
+    /// Card template for this list, overrides the user's template
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<CardTemplate>,
+    /// Weight of items in the pick (by item id), only set when lists are merged
+    #[serde(skip)]
+    pub weights: HashMap<String,f64>,
+    /// Index of the list each item came from (by item id), only set when lists are merged
+    #[serde(skip)]
+    pub sources: HashMap<String,usize>,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Template for rendering the front and back of a card. Fields are referenced as {{field}},
+/// {{#field}}...{{/field}} is only shown if the field is non-empty and {{^field}}...{{/field}}
+/// only if it is empty. The back may include the rendered front as {{FrontSide}}.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CardTemplate {
+    #[serde(default)]
+    pub front: String,
+    #[serde(default)]
+    pub back: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
 pub struct VocaScore {
     pub correct: HashMap<String,u32>,
     pub incorrect: HashMap<String,u32>,
     pub lastseen: HashMap<String,u64>,
-//    pub due: HashMap<String,u64>
+    #[serde(default)]
+    pub grade: HashMap<String,Grade>,
+    #[serde(default)]
+    pub ease: HashMap<String,f64>,
+    #[serde(default)]
+    pub interval: HashMap<String,u64>,
+    #[serde(default)]
+    pub due: HashMap<String,u64>,
+    /// For each item, the items it was confused with (and how often)
+    #[serde(default)]
+    pub confusions: HashMap<String,HashMap<String,u32>>,
+    /// Average time (in milliseconds) it took to answer an item correctly
+    #[serde(default)]
+    pub responsetime: HashMap<String,u64>,
+    /// Personal best (in points) per timed game
+    #[serde(default)]
+    pub personalbest: HashMap<String,u32>,
+    /// When new items were introduced in learning mode
+    #[serde(default)]
+    pub introduced: HashMap<String,u64>,
+    /// Items excluded from practice until they are restored (and when they were suspended)
+    #[serde(default)]
+    pub suspended: HashMap<String,u64>,
+    /// Items temporarily excluded from practice, until the given time
+    #[serde(default)]
+    pub buried: HashMap<String,u64>,
+    /// Items that are known and retired from practice (and when they were marked as known)
+    #[serde(default)]
+    pub known: HashMap<String,u64>,
+}
+
+/// Self-assessed grade for an item, from worst to best
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Grade {
+    Again,
+    Hard,
+    Good,
+    Easy
+}
+
+/// A single practice session, keeps track of its own limits and results
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Session {
+    pub started: u64,
+    pub ended: u64,
+    pub practised: u32,
+    pub correct: u32,
+    pub incorrect: u32,
+    /// Words that were answered correctly for the first time ever
+    pub learned: Vec<String>,
+    /// Words that were answered incorrectly during the session
+    pub revisit: Vec<String>,
+    #[serde(skip)]
+    pub maxcount: Option<u32>,
+    #[serde(skip)]
+    pub maxduration: Option<u64>,
+}
+
+/// Statistics of the daily reviews (over all lists), kept per user
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct ReviewStats {
+    /// Number of consecutive days with reviews, up to and including the last day
+    pub streak: u32,
+    pub longeststreak: u32,
+    /// The last day with reviews (in days since the epoch, UTC)
+    pub lastday: u64,
+    /// What was reviewed on each day (in days since the epoch, UTC)
+    pub history: BTreeMap<u64, DayStats>,
+}
+
+/// What was reviewed on a single day
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct DayStats {
+    pub reviews: u32,
+    pub new: u32,
+    pub correct: u32,
+}
+
+/// A single answered question in an exam
+#[derive(Serialize, Deserialize)]
+pub struct ExamQuestion {
+    pub word: String,
+    pub answer: String,
+    pub reference: String,
+    pub correct: bool,
+}
+
+/// The result of an exam, these are stored separately from the practice scores
+#[derive(Serialize, Deserialize)]
+pub struct ExamRecord {
+    pub list: String,
+    pub seed: u64,
+    pub started: u64,
+    /// The number of questions in the exam, questions that were not reached count as incorrect
+    #[serde(default)]
+    pub total: usize,
+    pub questions: Vec<ExamQuestion>,
 }
 
+/// A game that was interrupted, it can be resumed exactly where it was left off
+#[derive(Serialize, Deserialize, Default)]
+pub struct SavedGame {
+    /// The command line the game was started with
+    pub args: Vec<String>,
+    /// Match quiz: the items of the current round (by id)
+    #[serde(default)]
+    pub items: Vec<String>,
+    /// Match quiz: the order in which the answers are presented
+    #[serde(default)]
+    pub mappings: Vec<usize>,
+    /// Match quiz: the items that are already matched
+    #[serde(default)]
+    pub solved: Vec<usize>,
+    /// Exam: the questions answered so far, the remaining ones follow from the seed
+    #[serde(default)]
+    pub exam: Option<ExamRecord>,
+    /// The session so far
+    #[serde(default)]
+    pub session: Session,
+    /// The working directory the game was started in, relative paths in the command line are
+    /// resolved from there
+    #[serde(default)]
+    pub directory: Option<PathBuf>,
+}
+
+/// A word placed in a crossword puzzle
+pub struct CrosswordEntry {
+    /// Index of the item in the list of items the puzzle was generated from
+    pub index: usize,
+    /// The answer as it appears in the grid (upper case, letters only, without diacritics)
+    pub answer: String,
+    pub clue: String,
+    pub row: usize,
+    pub column: usize,
+    pub across: bool,
+    pub number: usize,
+}
+
+/// A cell in a crossword grid: None for a blank cell, otherwise the letter and the number of
+/// the word(s) starting there
+pub type CrosswordCell = Option<(char, Option<usize>)>;
+
+/// A crossword puzzle
+pub struct Crossword {
+    pub width: usize,
+    pub height: usize,
+    pub entries: Vec<CrosswordEntry>,
+}
+
+/// A word hidden in a word search puzzle
+pub struct WordSearchEntry {
+    /// Index of the item in the list of items the puzzle was generated from
+    pub index: usize,
+    /// The answer as it appears in the grid (see Crossword::normalise)
+    pub answer: String,
+    pub clue: String,
+    pub row: usize,
+    pub column: usize,
+    /// Direction as (row step, column step)
+    pub direction: (i64, i64),
+}
+
+/// A word search puzzle
+pub struct WordSearch {
+    pub grid: Vec<Vec<char>>,
+    pub entries: Vec<WordSearchEntry>,
+}
+
+/// Compass directions a word may run in, as (name, row step, column step)
+pub const DIRECTIONS: [(&str, i64, i64); 8] = [("e", 0, 1), ("s", 1, 0), ("se", 1, 1), ("ne", -1, 1), ("w", 0, -1), ("n", -1, 0), ("nw", -1, -1), ("sw", 1, -1)];
+
+/// Strategy for selecting distractors (wrong options) in multiple-choice questions
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DistractorStrategy {
+    /// Any item
+    Random,
+    /// Items sharing tags (e.g. the same part-of-speech)
+    Tags,
+    /// Items with a translation of similar length
+    Length,
+    /// Items with a similarly spelled word
+    Spelling,
+    /// Items with a similar phonetic transcription
+    Transcription,
+    /// Items the learner confused with this one before
+    Confused,
+    /// A combination of all of the above
+    Mixed,
+}
+
+/// The fields of an item that are used to prompt and to answer in games
+#[derive(Clone, PartialEq, Debug)]
+pub struct FieldPair {
+    pub prompt: String,
+    pub answer: String,
+}
+
+/// Names of the fields of VocaItem that can be used in games
+pub const FIELDS: [&str; 6] = ["word", "transcription", "translation", "example", "comment", "tags"];
+
+/// Initial ease factor for items that have not been graded yet
+pub const DEFAULT_EASE: f64 = 2.5;
+/// Lower bound for the ease factor
+pub const MIN_EASE: f64 = 1.3;
+const DAY: u64 = 86400;
+/// Correct answers that take longer than this (in milliseconds) count as weaker knowledge
+pub const SLOW_RESPONSE: u64 = 3000;
+
 ///we implement the Display trait so we can print VocaItems
 impl fmt::Display for VocaItem {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -57,6 +289,144 @@ impl fmt::Display for VocaItem {
     }
 }
 
+impl Grade {
+    /// Parse a grade from user input, accepts the number (1-4), the full name, or the
+    /// k (known, i.e. good) and i (incorrect, i.e. again) aliases. The aliases are lower case
+    /// only, an upper case K marks an item as known rather than grading it.
+    pub fn parse(input: &str) -> Option<Grade> {
+        match input {
+            "i" => return Some(Grade::Again),
+            "k" => return Some(Grade::Good),
+            _ => {}
+        }
+        match input.to_lowercase().as_str() {
+            "1" | "again" => Some(Grade::Again),
+            "2" | "hard" => Some(Grade::Hard),
+            "3" | "good" => Some(Grade::Good),
+            "4" | "easy" => Some(Grade::Easy),
+            _ => None
+        }
+    }
+
+    /// Is this grade considered a correct answer?
+    pub fn correct(self) -> bool {
+        self != Grade::Again
+    }
+}
+
+impl DistractorStrategy {
+    pub fn parse(input: &str) -> Option<DistractorStrategy> {
+        match input.to_lowercase().as_str() {
+            "random" => Some(DistractorStrategy::Random),
+            "tags" => Some(DistractorStrategy::Tags),
+            "length" => Some(DistractorStrategy::Length),
+            "spelling" => Some(DistractorStrategy::Spelling),
+            "transcription" => Some(DistractorStrategy::Transcription),
+            "confused" => Some(DistractorStrategy::Confused),
+            "mixed" => Some(DistractorStrategy::Mixed),
+            _ => None
+        }
+    }
+
+    /// How plausible is the candidate as a distractor for the item? (higher is more plausible),
+    /// the score data is only needed for the confused strategy
+    pub fn similarity(self, item: &VocaItem, candidate: &VocaItem, fields: &FieldPair, optscoredata: Option<&VocaScore>) -> f64 {
+        match self {
+            DistractorStrategy::Random => 0.0,
+            DistractorStrategy::Tags => {
+                let shared = item.tags.iter().filter(|tag| candidate.tags.contains(tag)).count();
+                let total = item.tags.len() + candidate.tags.len() - shared;
+                if total > 0 { shared as f64 / total as f64 } else { 0.0 }
+            },
+            DistractorStrategy::Length => {
+                let length = fields.answer(item).chars().count() as f64;
+                let candidatelength = fields.answer(candidate).chars().count() as f64;
+                1.0 / (1.0 + (length - candidatelength).abs())
+            },
+            DistractorStrategy::Spelling => stringsimilarity(&item.word.to_lowercase(), &candidate.word.to_lowercase()),
+            DistractorStrategy::Transcription => {
+                if item.transcription.is_empty() || candidate.transcription.is_empty() {
+                    0.0
+                } else {
+                    stringsimilarity(&item.transcription, &candidate.transcription)
+                }
+            },
+            DistractorStrategy::Confused => {
+                if let Some(scoredata) = optscoredata {
+                    let count = scoredata.confusioncount(item, candidate) as f64;
+                    count / (count + 1.0)
+                } else {
+                    0.0
+                }
+            },
+            DistractorStrategy::Mixed => {
+                //previous confusions always take precedence
+                let strategies = [DistractorStrategy::Tags, DistractorStrategy::Length, DistractorStrategy::Spelling, DistractorStrategy::Transcription];
+                strategies.iter().map(|strategy| strategy.similarity(item, candidate, fields, optscoredata)).sum::<f64>() / strategies.len() as f64
+                    + DistractorStrategy::Confused.similarity(item, candidate, fields, optscoredata)
+            }
+        }
+    }
+}
+
+impl FieldPair {
+    /// Create a new field pair, returns an error if one of the fields does not exist
+    pub fn new(prompt: &str, answer: &str) -> Result<FieldPair, String> {
+        for field in [prompt, answer].iter() {
+            if !isfieldname(field) {
+                return Err(format!("No such field: {} (expected one of: {}, or a custom field)", field, FIELDS.join(", ")));
+            }
+        }
+        if prompt == answer {
+            return Err("The prompt and answer fields must differ".to_string());
+        }
+        Ok(FieldPair { prompt: prompt.to_string(), answer: answer.to_string() })
+    }
+
+    /// Is this the default pair (word to translation)?
+    pub fn is_default(&self) -> bool {
+        self.prompt == "word" && self.answer == "translation"
+    }
+
+    /// Returns the value of the prompt field for the given item
+    pub fn prompt(&self, item: &VocaItem) -> String {
+        item.field(&self.prompt).unwrap_or_default()
+    }
+
+    /// Returns the value of the answer field for the given item
+    pub fn answer(&self, item: &VocaItem) -> String {
+        item.field(&self.answer).unwrap_or_default()
+    }
+
+    /// Can the item be used with this pair? (i.e. are both fields non-empty)
+    pub fn applies(&self, item: &VocaItem) -> bool {
+        !self.prompt(item).is_empty() && !self.answer(item).is_empty()
+    }
+}
+
+impl Default for FieldPair {
+    fn default() -> FieldPair {
+        FieldPair { prompt: "word".to_string(), answer: "translation".to_string() }
+    }
+}
+
+impl fmt::Display for FieldPair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}-{}", self.prompt, self.answer)
+    }
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Grade::Again => write!(f,"again"),
+            Grade::Hard => write!(f,"hard"),
+            Grade::Good => write!(f,"good"),
+            Grade::Easy => write!(f,"easy"),
+        }
+    }
+}
+
 impl VocaItem {
     pub fn id(&self) -> md5::Digest {
         md5::compute(self.word.as_bytes())
@@ -65,12 +435,17 @@ impl VocaItem {
         format!("{:x}",self.id())
     }
 
+    /// Does the item match any of the filters? A filter is either a tag or a field=value
+    /// condition (e.g. register=formal)
     pub fn filter(&self, filtertags: Option<&Vec<&str>>) -> bool {
         match filtertags {
             Some(tags) => match tags.is_empty() {
                false => {
                    //do the actual matching
-                   self.tags.iter().any(|tag| tags.contains(&tag.as_str()))
+                   tags.iter().any(|tag| match tag.find('=') {
+                       Some(pos) => self.field(&tag[..pos]).map(|value| value.to_lowercase() == tag[pos+1..].to_lowercase()).unwrap_or(false),
+                       None => self.tags.iter().any(|t| t == tag)
+                   })
                },
                true => true
             },
@@ -78,6 +453,63 @@ impl VocaItem {
         }
     }
 
+    ///Returns the value of a field by name, the example is returned without cloze markup. Names
+    ///other than the standard fields refer to custom fields.
+    pub fn field(&self, name: &str) -> Option<String> {
+        match name {
+            "word" => Some(self.word.clone()),
+            "transcription" => Some(self.transcription.clone()),
+            "translation" => Some(self.translation.clone()),
+            "example" => Some(self.plainexample()),
+            "comment" => Some(self.comment.clone()),
+            "tags" => Some(self.tags.join(", ")),
+            _ => self.extra.get(name).cloned()
+        }
+    }
+
+    ///Returns the example with any cloze markup ({{...}}) removed
+    pub fn plainexample(&self) -> String {
+        Regex::new(r"\{\{(.*?)\}\}").unwrap().replace_all(&self.example, "$1").to_string()
+    }
+
+    ///Turns the example into a cloze (fill-in-the-blank) question, returns the example with the
+    ///target word blanked out and the word that fills the blank. The part to blank can be marked
+    ///explicitly in the example as {{word}}, otherwise we look for the word itself or, failing
+    ///that, a form of it that shares its stem. Returns None if there is no usable example.
+    pub fn cloze(&self) -> Option<(String,String)> {
+        let blank = "_____";
+        let markup = Regex::new(r"\{\{(.*?)\}\}").unwrap();
+        if let Some(capture) = markup.captures(&self.example) {
+            let answer = capture[1].to_string();
+            let question = markup.replace_all(&self.example, |captures: &regex::Captures| {
+                if captures[1] == answer { blank.to_string() } else { captures[1].to_string() }
+            }).to_string();
+            return Some((question, answer));
+        }
+        if self.word.is_empty() {
+            return None;
+        }
+        let word = Regex::new(&format!(r"(?i)\b{}\b", regex::escape(&self.word))).unwrap();
+        if let Some(found) = word.find(&self.example) {
+            let answer = found.as_str().to_string();
+            return Some((word.replace_all(&self.example, blank).to_string(), answer));
+        }
+        //look for an inflected form: the token sharing the longest prefix with the word
+        let lowerword: Vec<char> = self.word.to_lowercase().chars().collect();
+        let minprefix = std::cmp::max(3, lowerword.len().saturating_sub(2));
+        let mut best: Option<(usize, &str)> = None;
+        for token in Regex::new(r"\w+").unwrap().find_iter(&self.example) {
+            let prefix = token.as_str().to_lowercase().chars().zip(lowerword.iter()).take_while(|(a,b)| a == *b).count();
+            if prefix >= minprefix && best.map(|(bestprefix,_)| prefix > bestprefix).unwrap_or(true) {
+                best = Some((prefix, token.as_str()));
+            }
+        }
+        best.map(|(_, answer)| {
+            let form = Regex::new(&format!(r"\b{}\b", regex::escape(answer))).unwrap();
+            (form.replace_all(&self.example, blank).to_string(), answer.to_string())
+        })
+    }
+
     ///Prints a vocaitem
     pub fn print(self: &VocaItem, phon: bool, translation: bool, example: bool) {
         println!("{}", self.word);
@@ -85,7 +517,7 @@ impl VocaItem {
             println!("{}", self.transcription);
         }
         if example {
-            println!("{}", self.example);
+            println!("{}", self.plainexample());
         }
         if translation {
             println!("{}", self.translation);
@@ -101,9 +533,45 @@ impl VocaList {
         Ok(data)
     }
 
+    /// Merge several lists into one so they can be practised together, the items of each list
+    /// are weighted with the associated weight. The template of the first list is used. Items
+    /// are identified by their word, so a word that occurs in several lists is only taken from
+    /// the first of them (see source()).
+    pub fn merge(lists: Vec<(VocaList, f64)>) -> VocaList {
+        let mut merged = VocaList { items: Vec::new(), template: None, weights: HashMap::new(), sources: HashMap::new() };
+        for (index, (list, weight)) in lists.into_iter().enumerate() {
+            if merged.template.is_none() {
+                merged.template = list.template;
+            }
+            for item in list.items {
+                let id = item.id_as_string();
+                if merged.sources.get(&id).map(|source| *source != index).unwrap_or(false) {
+                    continue;
+                }
+                if weight != 1.0 {
+                    merged.weights.insert(id.clone(), weight);
+                }
+                merged.sources.insert(id, index);
+                merged.items.push(item);
+            }
+        }
+        merged
+    }
+
+    /// The index of the list an item of merged lists came from (0 if the list was not merged)
+    pub fn source(&self, item: &VocaItem) -> usize {
+        *self.sources.get(&item.id_as_string()).unwrap_or(&0)
+    }
+
+    /// The weight of an item in the pick
+    pub fn weight(&self, item: &VocaItem) -> f64 {
+        *self.weights.get(&item.id_as_string()).unwrap_or(&1.0)
+    }
+
     /// Add a new item to the vocabulary list
-    pub fn append(&mut self, word: String, translation: Option<&str>, transcription: Option<&str>, example: Option<&str>, comment: Option<&str>, tags: Option<&Vec<&str>>) {
-        let tags: Vec<String> = if let Some(ref tags) = tags {
+    #[allow(clippy::too_many_arguments)]
+    pub fn append(&mut self, word: String, translation: Option<&str>, transcription: Option<&str>, example: Option<&str>, comment: Option<&str>, tags: Option<&Vec<&str>>, extra: BTreeMap<String, String>) {
+        let tags: Vec<String> = if let Some(tags) = tags {
             tags.iter()
                 .map(|s| { s.to_string() })
                 .collect()
@@ -111,12 +579,13 @@ impl VocaList {
             Vec::new()
         };
         let item = VocaItem {
-            word: word,
-            translation: translation.map(|s:&str| s.to_string()).unwrap_or(String::new()),
-            transcription: transcription.map(|s:&str| s.to_string()).unwrap_or(String::new()),
-            example: example.map(|s:&str| s.to_string()).unwrap_or(String::new()),
-            comment: comment.map(|s:&str| s.to_string()).unwrap_or(String::new()),
-            tags: tags,
+            word,
+            translation: translation.map(|s:&str| s.to_string()).unwrap_or_default(),
+            transcription: transcription.map(|s:&str| s.to_string()).unwrap_or_default(),
+            example: example.map(|s:&str| s.to_string()).unwrap_or_default(),
+            comment: comment.map(|s:&str| s.to_string()).unwrap_or_default(),
+            tags,
+            extra,
         };
         self.items.push(item);
     }
@@ -127,7 +596,8 @@ impl VocaList {
     }
 
     /// Show the contents of the Vocabulary List; prints to to standard output
-    pub fn show(&self, withtranslation: bool, withtranscription: bool, filtertags: Option<&Vec<&str>>, withtags: bool, withexample: bool, withcomment: bool) {
+    #[allow(clippy::too_many_arguments)]
+    pub fn show(&self, withtranslation: bool, withtranscription: bool, filtertags: Option<&Vec<&str>>, withtags: bool, withexample: bool, withcomment: bool, withextra: bool) {
         for item in self.items.iter() {
             if item.filter(filtertags) {
                 print!("{}", item);
@@ -144,62 +614,156 @@ impl VocaList {
                         }
                     }
                 }
+                if withextra {
+                    for (key, value) in item.extra.iter() {
+                        print!("\t{}={}", key, value);
+                    }
+                }
                 println!()
             }
         }
     }
 
-    ///Output all data as CSV
+    /// Returns the names of all custom fields used in the list, in alphabetical order
+    pub fn extrafields(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.items.iter().flat_map(|item| item.extra.keys().map(|key| key.as_str())).collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Returns the letters used in the words of the list (as in Crossword::normalise), with
+    /// duplicates, so more frequent letters occur more often
+    pub fn alphabet(&self) -> Vec<char> {
+        self.items.iter().flat_map(|item| Crossword::normalise(&item.word).chars().collect::<Vec<char>>()).collect()
+    }
+
+    ///Output all data as CSV, with a header row. Tags are joined into a single column and
+    ///every custom field gets a column of its own.
     pub fn csv(&self, filtertags: Option<&Vec<&str>>) -> Result<(), Box<dyn Error>> {
         let mut wtr = csv::WriterBuilder::new()
-            .flexible(true)
-            .has_headers(false)
             .from_writer(io::stdout());
+        let extrafields = self.extrafields();
+        let mut header: Vec<&str> = FIELDS.to_vec();
+        header.extend(extrafields.iter());
+        wtr.write_record(&header)?;
         for item in self.items.iter() {
             if item.filter(filtertags) {
-                wtr.serialize(item)?;
+                let record: Vec<String> = header.iter().map(|name| match *name {
+                    "example" => item.example.clone(), //keep the cloze markup
+                    "tags" => item.tags.join(","),
+                    _ => item.field(name).unwrap_or_default()
+                }).collect();
+                wtr.write_record(&record)?;
             }
         };
         wtr.flush()?;
         Ok(())
     }
 
-    ///Select a word
-    pub fn pick(&self, mut optscoredata: Option<&mut VocaScore>, filtertags: Option<&Vec<&str>>, seen: bool) -> &VocaItem {
-        let sum: f64 = self.items.iter().map(|item| {
-            if item.filter(filtertags) {
-                if let Some(ref scoredata) = optscoredata {
-                    scoredata.score(item.id_as_string().as_str())
-                } else {
-                    1.0
-                }
+    ///Select a word, returns None if there is nothing to pick from
+    pub fn pick(&self, optscoredata: Option<&mut VocaScore>, filtertags: Option<&Vec<&str>>, seen: bool) -> Option<&VocaItem> {
+        self.pickwith(optscoredata, filtertags, seen, |_| true)
+    }
+
+    ///The items that can be practised: those that satisfy the given condition and that are not
+    ///suspended, buried or known
+    pub fn available(&self, optscoredata: Option<&VocaScore>, filtertags: Option<&Vec<&str>>, condition: impl Fn(&VocaItem) -> bool) -> Vec<&VocaItem> {
+        self.items.iter().filter(|item| {
+            item.filter(filtertags) && condition(item) && !optscoredata.map(|scoredata| scoredata.excluded(item)).unwrap_or(false)
+        }).collect()
+    }
+
+    ///Select a word amongst the items that satisfy the given condition, returns None if there
+    ///are no such items
+    pub fn pickwith(&self, mut optscoredata: Option<&mut VocaScore>, filtertags: Option<&Vec<&str>>, seen: bool, condition: impl Fn(&VocaItem) -> bool) -> Option<&VocaItem> {
+        let candidates: Vec<(&VocaItem, f64)> = self.available(optscoredata.as_deref(), filtertags, condition).into_iter().map(|item| {
+            if let Some(ref scoredata) = optscoredata {
+                (item, scoredata.score(item.id_as_string().as_str()) * self.weight(item))
             } else {
-                0.0
+                (item, self.weight(item))
             }
-        }).sum();
+        }).collect();
+        let sum: f64 = candidates.iter().map(|(_, score)| score).sum();
         let choice: f64 = rand::random::<f64>() * sum;
         let mut score: f64 = 0.0; //cummulative score
-        let mut choiceindex: usize = 0;
-        for (i, item) in self.items.iter().enumerate() {
-            if item.filter(filtertags) {
-                if let Some(ref scoredata) = optscoredata {
-                    score += scoredata.score(item.id_as_string().as_str());
-                } else {
-                    score += 1.0;
-                }
-                if score >= choice {
-                    choiceindex = i;
-                    break;
-                }
+        let mut vocaitem: &VocaItem = candidates.last()?.0; //in case rounding leaves the score just short
+        for (item, itemscore) in candidates.iter() {
+            score += itemscore;
+            if score >= choice {
+                vocaitem = item;
+                break;
             }
         }
-        let vocaitem = &self.items[choiceindex];
         if seen {
             if let Some(ref mut scoredata) = optscoredata {
                 scoredata.seen(vocaitem);
             }
         }
-        vocaitem
+        Some(vocaitem)
+    }
+
+    ///Select (at most) count distinct items amongst the items that satisfy the given condition,
+    ///there may be fewer if there are not enough such items
+    pub fn pickdistinct(&self, mut optscoredata: Option<&mut VocaScore>, filtertags: Option<&Vec<&str>>, count: usize, seen: bool, condition: impl Fn(&VocaItem) -> bool) -> Vec<&VocaItem> {
+        let mut vocaitems: Vec<&VocaItem> = Vec::new();
+        while vocaitems.len() < count {
+            match self.pickwith(optscoredata.as_deref_mut(), filtertags, seen, |item| condition(item) && !vocaitems.iter().any(|x| x.id() == item.id())) {
+                Some(vocaitem) => vocaitems.push(vocaitem),
+                None => break //there are no more distinct items
+            }
+        }
+        vocaitems
+    }
+
+    ///Select a fixed random sample of (at most) count items without repetition, the same seed
+    ///always gives the same sample
+    pub fn sample(&self, count: usize, filtertags: Option<&Vec<&str>>, seed: u64) -> Vec<&VocaItem> {
+        let mut rng: StdRng = SeedableRng::from_seed(&[seed as usize][..]);
+        let mut candidates: Vec<&VocaItem> = self.items.iter().filter(|item| item.filter(filtertags)).collect();
+        rng.shuffle(&mut candidates);
+        candidates.truncate(count);
+        candidates
+    }
+
+    ///Select up to count distinct distractors for a multiple-choice question about the given item,
+    ///the most plausible candidates according to the strategy are preferred, ties are broken
+    ///randomly. Items with the same answer as the item itself are never selected.
+    pub fn distractors(&self, item: &VocaItem, count: usize, filtertags: Option<&Vec<&str>>, fields: &FieldPair, strategy: DistractorStrategy, optscoredata: Option<&VocaScore>) -> Vec<&VocaItem> {
+        let answer = fields.answer(item);
+        let mut candidates: Vec<&VocaItem> = self.items.iter().filter(|candidate| {
+            candidate.filter(filtertags) && fields.applies(candidate) && candidate.word != item.word && fields.answer(candidate) != answer
+        }).collect();
+        rand::thread_rng().shuffle(&mut candidates);
+        let mut candidates: Vec<(f64, &VocaItem)> = candidates.into_iter().map(|candidate| {
+            (strategy.similarity(item, candidate, fields, optscoredata), candidate)
+        }).collect();
+        candidates.sort_by(|(a,_), (b,_)| b.partial_cmp(a).expect("similarity must be a number")); //stable, so the shuffle decides ties
+        let mut distractors: Vec<&VocaItem> = Vec::new();
+        for (_, candidate) in candidates {
+            if distractors.len() >= count {
+                break;
+            }
+            if !distractors.iter().any(|distractor| fields.answer(distractor) == fields.answer(candidate)) {
+                distractors.push(candidate);
+            }
+        }
+        distractors
+    }
+
+    ///Lookup an item by its identifier
+    pub fn findbyid(&self, id: &str) -> Option<&VocaItem> {
+        self.items.iter().find(|item| item.id_as_string() == id)
+    }
+
+    ///Lookup the item a translation belongs to
+    pub fn findbytranslation(&self, translation: &str, filtertags: Option<&Vec<&str>>) -> Option<&VocaItem> {
+        self.findbyfield("translation", translation, filtertags)
+    }
+
+    ///Lookup the item whose field matches the value (in the same way answers are checked)
+    pub fn findbyfield(&self, field: &str, value: &str, filtertags: Option<&Vec<&str>>) -> Option<&VocaItem> {
+        self.items.iter().find(|item| item.filter(filtertags) && checktranslation(value, &item.field(field).unwrap_or_default()))
     }
 
     ///Lookup a word
@@ -230,11 +794,66 @@ impl VocaScore {
     }
 
     ///Return the 'score' for an item, this corresponds to the probability it is presented, so
-    ///the lower the score, the better a word is known
+    ///the lower the score, the better a word is known. Items that are answered correctly but
+    ///slowly are considered less well known (up to twice the score).
     pub fn score(&self, id: &str) -> f64 {
-        let correct = *self.correct.get(id).or(Some(&0)).unwrap() + 1;
-        let incorrect = *self.incorrect.get(id).or(Some(&0)).unwrap() + 1;
-        incorrect as f64 / correct as f64
+        let correct = *self.correct.get(id).unwrap_or(&0) + 1;
+        let incorrect = *self.incorrect.get(id).unwrap_or(&0) + 1;
+        let ease = *self.ease.get(id).unwrap_or(&DEFAULT_EASE);
+        let slowness = match self.responsetime.get(id) {
+            Some(responsetime) if *responsetime > SLOW_RESPONSE => (*responsetime as f64 / SLOW_RESPONSE as f64).min(2.0),
+            _ => 1.0
+        };
+        (incorrect as f64 / correct as f64) * (DEFAULT_EASE / ease) * slowness
+    }
+
+    ///Merge the scores of one of several merged lists (see VocaList::merge()) into these, used
+    ///when several lists are practised together. Only the scores of the items that were taken
+    ///from that list are merged. Personal bests are kept per list, so the best of either is
+    ///retained.
+    pub fn merge(&mut self, other: VocaScore, merged: &VocaList, index: usize) {
+        for item in merged.items.iter().filter(|item| merged.source(item) == index) {
+            self.updateitem(&other, item);
+        }
+        for (game, points) in other.personalbest {
+            let best = self.personalbest.entry(game).or_insert(0);
+            *best = std::cmp::max(*best, points);
+        }
+    }
+
+    ///Take over the scores of the items that were taken from one of several merged lists from
+    ///the merged scores (see merge()), personal bests from a merged session are not taken over
+    ///as they do not belong to a single list
+    pub fn update(&mut self, mergedscores: &VocaScore, merged: &VocaList, index: usize) {
+        for item in merged.items.iter().filter(|item| merged.source(item) == index) {
+            self.updateitem(mergedscores, item);
+        }
+    }
+
+    ///Take over all scores of a single item from other score data
+    pub fn updateitem(&mut self, other: &VocaScore, item: &VocaItem) {
+        let id = item.id_as_string();
+        takeover(&mut self.correct, &other.correct, &id);
+        takeover(&mut self.incorrect, &other.incorrect, &id);
+        takeover(&mut self.lastseen, &other.lastseen, &id);
+        takeover(&mut self.grade, &other.grade, &id);
+        takeover(&mut self.ease, &other.ease, &id);
+        takeover(&mut self.interval, &other.interval, &id);
+        takeover(&mut self.due, &other.due, &id);
+        takeover(&mut self.confusions, &other.confusions, &id);
+        takeover(&mut self.responsetime, &other.responsetime, &id);
+        takeover(&mut self.introduced, &other.introduced, &id);
+        takeover(&mut self.suspended, &other.suspended, &id);
+        takeover(&mut self.buried, &other.buried, &id);
+        takeover(&mut self.known, &other.known, &id);
+    }
+
+    ///Copy all scores of a single item, an answer can be undone by restoring these with
+    ///updateitem()
+    pub fn snapshot(&self, item: &VocaItem) -> VocaScore {
+        let mut snapshot = VocaScore::default();
+        snapshot.updateitem(self, item);
+        snapshot
     }
 
     pub fn seen(&mut self, item: &VocaItem) {
@@ -252,70 +871,1278 @@ impl VocaScore {
             *self.incorrect.entry(id).or_insert(0) += 1;
         }
     }
-}
 
-impl Default for VocaScore {
-    fn default() -> VocaScore {
-        VocaScore {
-            correct: HashMap::new(),
-            incorrect: HashMap::new(),
-            lastseen: HashMap::new()
+    ///Record a result along with how long it took to answer (in milliseconds), the time only
+    ///counts for correct answers
+    pub fn addtimedscore(&mut self, item: &VocaItem, correct: bool, millis: u64) {
+        self.addscore(item, correct);
+        if correct {
+            self.addresponsetime(item, millis);
         }
     }
-}
 
+    ///Record how long it took to answer an item (in milliseconds), we keep a moving average in
+    ///which recent answers weigh more
+    pub fn addresponsetime(&mut self, item: &VocaItem, millis: u64) {
+        let average = self.responsetime.entry(item.id_as_string()).or_insert(millis);
+        *average = (*average * 2 + millis) / 3;
+    }
 
-/// Return the default data directory
-pub fn defaultdatadir() -> PathBuf {
-    PathBuf::from(dirs::config_dir().expect("Unable to find configuration dir")).join("vocajeux").join("data")
-}
-///
-/// Return the default score directory
-pub fn defaultscoredir() -> PathBuf {
-    PathBuf::from(dirs::config_dir().expect("Unable to find configuration dir")).join("vocajeux").join("scores")
-}
+    ///Record that a new item was introduced in learning mode
+    pub fn introduce(&mut self, item: &VocaItem) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("Unable to get time").as_secs();
+        self.introduced.insert(item.id_as_string(), now);
+    }
 
-pub fn getdatafile(name: &str, datapath: PathBuf) -> Option<PathBuf> {
-    let mut filename: String = name.to_owned();
-    filename.push_str(".json");
-    let datafile = datapath.join(filename);
-    match datafile.exists() {
-        true => Some(datafile),
-        false => None
+    ///How many new items were introduced today (UTC)
+    pub fn introducedtoday(&self) -> usize {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("Unable to get time").as_secs();
+        let today = now - now % DAY;
+        self.introduced.values().filter(|introduced| **introduced >= today).count()
     }
-}
 
-pub fn getscorefile(name: &str, scorepath: PathBuf, accesskey: Option<&str>) -> PathBuf {
-    let mut filename: String = if name.ends_with(".json") {
-        name[..name.len()-5].to_string()
-    } else {
-        name.to_string()
-    };
-    if let Some(accesskey) = accesskey {
-        filename.push_str(".");
-        filename.push_str(accesskey);
+    ///Is the item due for review? That is the case if its interval has passed, or if it was
+    ///seen before but never scheduled
+    pub fn isdue(&self, item: &VocaItem) -> bool {
+        let id = item.id_as_string();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("Unable to get time").as_secs();
+        match self.due.get(&id) {
+            Some(due) => *due <= now,
+            None => self.lastseen.contains_key(&id)
+        }
+    }
+
+    ///Record the points of a timed game, returns true if this is a new personal best
+    pub fn addpersonalbest(&mut self, game: &str, points: u32) -> bool {
+        let best = self.personalbest.entry(game.to_string()).or_insert(0);
+        if points > *best {
+            *best = points;
+            true
+        } else {
+            false
+        }
     }
-    filename.push_str(".score.json");
-    scorepath.join(filename)
-}
 
+    ///Exclude an item from practice until it is restored
+    pub fn suspend(&mut self, item: &VocaItem) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("Unable to get time").as_secs();
+        self.suspended.insert(item.id_as_string(), now);
+    }
 
-/// Returns an index of available vocabulary sets
-pub fn getdataindex(configpath_opt: Option<PathBuf>) -> Vec<PathBuf> {
-    let mut index: Vec<PathBuf> = Vec::new();
-    let configpath;
-    if let Some(configpath_some) = configpath_opt {
-        configpath = configpath_some;
-    } else {
-        configpath = dirs::config_dir().expect("Unable to find configuration dir");
+    ///Exclude an item from practice until the given time (seconds since the epoch)
+    pub fn bury(&mut self, item: &VocaItem, until: u64) {
+        self.buried.insert(item.id_as_string(), until);
     }
-    let datapath = PathBuf::from(configpath).join("vocajeux").join("data");
-    if datapath.exists() {
-        for file in datapath.read_dir().expect("Unable to read dir") {
-            if let Ok(file) = file {
-                index.push(file.path());
+
+    ///Mark an item as known, retiring it from practice
+    pub fn markknown(&mut self, item: &VocaItem) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("Unable to get time").as_secs();
+        self.known.insert(item.id_as_string(), now);
+    }
+
+    ///Bring a suspended, buried or known item back into practice, returns false if it wasn't
+    ///excluded in the first place
+    pub fn restore(&mut self, item: &VocaItem) -> bool {
+        let id = item.id_as_string();
+        let suspended = self.suspended.remove(&id).is_some();
+        let buried = self.buried.remove(&id).is_some();
+        let known = self.known.remove(&id).is_some();
+        suspended || buried || known
+    }
+
+    ///Is the item excluded from practice (suspended, buried or known)?
+    pub fn excluded(&self, item: &VocaItem) -> bool {
+        let id = item.id_as_string();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("Unable to get time").as_secs();
+        self.suspended.contains_key(&id) || self.known.contains_key(&id) || self.buried.get(&id).map(|until| *until > now).unwrap_or(false)
+    }
+
+    ///Record that the learner confused an item with another one (i.e. gave the answer belonging
+    ///to the other item)
+    pub fn addconfusion(&mut self, item: &VocaItem, confusedwith: &VocaItem) {
+        if item.id() != confusedwith.id() {
+            *self.confusions.entry(item.id_as_string()).or_default().entry(confusedwith.id_as_string()).or_insert(0) += 1;
+        }
+    }
+
+    ///How often two items were confused with eachother (in either direction)
+    pub fn confusioncount(&self, item: &VocaItem, other: &VocaItem) -> u32 {
+        let (id, otherid) = (item.id_as_string(), other.id_as_string());
+        let count = self.confusions.get(&id).and_then(|confusions| confusions.get(&otherid)).unwrap_or(&0);
+        let reversecount = self.confusions.get(&otherid).and_then(|confusions| confusions.get(&id)).unwrap_or(&0);
+        count + reversecount
+    }
+
+    ///Returns all pairs of confused items (by identifier) and how often they were confused (in
+    ///either direction), the most confused pairs come first
+    pub fn confusablepairs(&self) -> Vec<(String,String,u32)> {
+        let mut pairs: HashMap<(String,String),u32> = HashMap::new();
+        for (id, confusions) in self.confusions.iter() {
+            for (otherid, count) in confusions.iter() {
+                let key = if id < otherid { (id.clone(), otherid.clone()) } else { (otherid.clone(), id.clone()) };
+                *pairs.entry(key).or_insert(0) += count;
             }
         }
+        let mut pairs: Vec<(String,String,u32)> = pairs.into_iter().map(|((id, otherid), count)| (id, otherid, count)).collect();
+        pairs.sort_by(|a,b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+        pairs
+    }
+
+    ///Add a self-assessed grade along with how long it took to recall the item (in milliseconds),
+    ///the time only counts if the item was recalled
+    pub fn addtimedgrade(&mut self, item: &VocaItem, grade: Grade, millis: u64) {
+        self.addgrade(item, grade);
+        if grade.correct() {
+            self.addresponsetime(item, millis);
+        }
+    }
+
+    ///Add a self-assessed grade for an item, this counts as a correct or incorrect answer and
+    ///updates the ease factor and the interval after which the item is due again
+    pub fn addgrade(&mut self, item: &VocaItem, grade: Grade) {
+        let id: String = item.id_as_string();
+        self.addscore(item, grade.correct());
+        let ease = *self.ease.get(&id).unwrap_or(&DEFAULT_EASE);
+        let interval = *self.interval.get(&id).unwrap_or(&0);
+        let (ease, interval) = match grade {
+            Grade::Again => (ease - 0.2, 0),
+            Grade::Hard => (ease - 0.15, std::cmp::max(DAY, (interval as f64 * 1.2) as u64)),
+            Grade::Good => (ease, if interval == 0 { DAY } else { (interval as f64 * ease) as u64 }),
+            Grade::Easy => (ease + 0.15, if interval == 0 { 4 * DAY } else { (interval as f64 * ease * 1.3) as u64 }),
+        };
+        let ease = if ease < MIN_EASE { MIN_EASE } else { ease };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("Unable to get time").as_secs();
+        self.ease.insert(id.clone(), ease);
+        self.interval.insert(id.clone(), interval);
+        self.due.insert(id.clone(), now + interval);
+        self.grade.insert(id, grade);
+    }
+}
+
+impl Session {
+    /// Start a new session, optionally limited to a number of items and/or a duration in seconds
+    pub fn new(maxcount: Option<u32>, maxduration: Option<u64>) -> Session {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("Unable to get time").as_secs();
+        Session {
+            started: now,
+            ended: now,
+            maxcount,
+            maxduration,
+            ..Default::default()
+        }
+    }
+
+    /// Record an answer, call this after the score has been added to the score data (if any)
+    pub fn record(&mut self, item: &VocaItem, correct: bool, optscoredata: Option<&VocaScore>) {
+        self.practised += 1;
+        if correct {
+            self.correct += 1;
+            if let Some(scoredata) = optscoredata {
+                if scoredata.correct.get(&item.id_as_string()) == Some(&1) && !self.learned.contains(&item.word) {
+                    self.learned.push(item.word.clone());
+                }
+            }
+        } else {
+            self.incorrect += 1;
+            if !self.revisit.contains(&item.word) {
+                self.revisit.push(item.word.clone());
+            }
+        }
+        self.ended = SystemTime::now().duration_since(UNIX_EPOCH).expect("Unable to get time").as_secs();
+    }
+
+    /// Has the session reached one of its limits?
+    pub fn done(&self) -> bool {
+        if let Some(maxcount) = self.maxcount {
+            if self.practised >= maxcount {
+                return true;
+            }
+        }
+        if let Some(maxduration) = self.maxduration {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("Unable to get time").as_secs();
+            if now - self.started >= maxduration {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Continue an earlier session: its results are taken over and the time spent in it counts
+    /// towards this one
+    pub fn resume(&mut self, earlier: &Session) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("Unable to get time").as_secs();
+        self.started = now - (earlier.ended - earlier.started);
+        self.ended = now;
+        self.practised = earlier.practised;
+        self.correct = earlier.correct;
+        self.incorrect = earlier.incorrect;
+        self.learned = earlier.learned.clone();
+        self.revisit = earlier.revisit.clone();
+    }
+
+    /// Mark the session as ended
+    pub fn finish(&mut self) {
+        self.ended = SystemTime::now().duration_since(UNIX_EPOCH).expect("Unable to get time").as_secs();
+    }
+
+    /// Ratio of correct answers
+    pub fn accuracy(&self) -> f64 {
+        if self.practised > 0 {
+            self.correct as f64 / self.practised as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Prints a summary of the session to standard output
+    pub fn print(&self) {
+        let duration = self.ended - self.started;
+        println!("Items practised: {}", self.practised);
+        println!("Accuracy:        {:.0}% ({} correct, {} incorrect)", self.accuracy() * 100.0, self.correct, self.incorrect);
+        println!("Time spent:      {}m{:02}s", duration / 60, duration % 60);
+        if !self.learned.is_empty() {
+            println!("Newly learned:   {}", self.learned.join(", "));
+        }
+        if !self.revisit.is_empty() {
+            println!("To revisit:      {}", self.revisit.join(", "));
+        }
+    }
+
+    /// Save the session summary as JSON
+    pub fn save(&self, filename: &str) -> std::io::Result<()> {
+        let data: String = serde_json::to_string(self)?;
+        fs::write(filename, data)
+    }
+}
+
+impl ExamRecord {
+    pub fn new(list: &str, seed: u64, total: usize) -> ExamRecord {
+        ExamRecord {
+            list: list.to_string(),
+            seed,
+            started: SystemTime::now().duration_since(UNIX_EPOCH).expect("Unable to get time").as_secs(),
+            total,
+            questions: Vec::new(),
+        }
+    }
+
+    /// Load an exam record
+    pub fn load(filename: &str) -> Result<ExamRecord, Box<dyn Error>> {
+        let data = fs::read_to_string(filename)?;
+        let data: ExamRecord = serde_json::from_str(data.as_str())?; //(shadowing)
+        Ok(data)
+    }
+
+    /// Save an exam record
+    pub fn save(&self, filename: &str) -> std::io::Result<()> {
+        let data: String = serde_json::to_string(self)?;
+        fs::write(filename, data)
+    }
+
+    /// Number of correctly answered questions
+    pub fn correct(&self) -> usize {
+        self.questions.iter().filter(|question| question.correct).count()
+    }
+
+    /// The final grade as a percentage of all questions in the exam (older records without a
+    /// total are graded on the answered questions)
+    pub fn grade(&self) -> f64 {
+        let total = self.total.max(self.questions.len());
+        if total == 0 {
+            0.0
+        } else {
+            100.0 * self.correct() as f64 / total as f64
+        }
+    }
+}
+
+impl Crossword {
+    /// Normalises a word for use in the grid: upper case, letters only, without diacritics
+    pub fn normalise(word: &str) -> String {
+        stripdiacritics(word).chars().filter(|c| c.is_alphabetic()).flat_map(|c| c.to_uppercase()).collect()
+    }
+
+    /// Generates a puzzle from the given items and clues (one for each item), placing at most
+    /// count words. Longer words are placed first, every following word must cross a word that
+    /// is already placed; the placement with the most crossings is preferred. Words that can not
+    /// be placed are skipped.
+    pub fn generate(items: &[&VocaItem], clues: &[String], count: usize) -> Crossword {
+        let mut order: Vec<usize> = (0..items.len()).filter(|i| Crossword::normalise(&items[*i].word).chars().count() > 1).collect();
+        order.sort_by_key(|i| std::cmp::Reverse(Crossword::normalise(&items[*i].word).chars().count()));
+        let maxsize: i64 = order.first().map(|i| Crossword::normalise(&items[*i].word).chars().count() as i64).unwrap_or(0).max(20);
+        let mut cells: HashMap<(i64,i64), char> = HashMap::new();
+        let mut placed: Vec<(usize, String, i64, i64, bool)> = Vec::new();
+        for index in order {
+            if placed.len() >= count {
+                break;
+            }
+            let answer = Crossword::normalise(&items[index].word);
+            if placed.iter().any(|(_, other, _, _, _)| *other == answer) {
+                continue;
+            }
+            let letters: Vec<char> = answer.chars().collect();
+            if placed.is_empty() {
+                for (i, c) in letters.iter().enumerate() {
+                    cells.insert((0, i as i64), *c);
+                }
+                placed.push((index, answer, 0, 0, true));
+                continue;
+            }
+            //find the best placement that crosses an existing letter
+            let mut best: Option<(usize, i64, i64, i64, bool)> = None; //crossings, area, row, column, across
+            for (&(row, column), &c) in cells.iter() {
+                for (i, _) in letters.iter().enumerate().filter(|(_, l)| **l == c) {
+                    for across in [true, false].iter() {
+                        let (startrow, startcolumn) = if *across { (row, column - i as i64) } else { (row - i as i64, column) };
+                        if let Some(crossings) = Crossword::fits(&cells, &letters, startrow, startcolumn, *across) {
+                            let (minrow, maxrow, mincolumn, maxcolumn) = Crossword::bounds(&cells, &letters, startrow, startcolumn, *across);
+                            if maxrow - minrow >= maxsize || maxcolumn - mincolumn >= maxsize {
+                                continue;
+                            }
+                            let area = (maxrow - minrow + 1) * (maxcolumn - mincolumn + 1);
+                            let candidate = (crossings, area, startrow, startcolumn, *across);
+                            //the hashmap iteration order is random, so we break all ties explicitly
+                            let better = match best {
+                                None => true,
+                                Some(best) => (candidate.0, -candidate.1, -candidate.2, -candidate.3, candidate.4) > (best.0, -best.1, -best.2, -best.3, best.4)
+                            };
+                            if better {
+                                best = Some(candidate);
+                            }
+                        }
+                    }
+                }
+            }
+            if let Some((_, _, row, column, across)) = best {
+                for (i, c) in letters.iter().enumerate() {
+                    let position = if across { (row, column + i as i64) } else { (row + i as i64, column) };
+                    cells.insert(position, *c);
+                }
+                placed.push((index, answer, row, column, across));
+            }
+        }
+        //translate to grid coordinates and number the entries in reading order
+        let minrow = cells.keys().map(|(row, _)| *row).min().unwrap_or(0);
+        let mincolumn = cells.keys().map(|(_, column)| *column).min().unwrap_or(0);
+        let width = cells.keys().map(|(_, column)| (column - mincolumn + 1) as usize).max().unwrap_or(0);
+        let height = cells.keys().map(|(row, _)| (row - minrow + 1) as usize).max().unwrap_or(0);
+        let mut entries: Vec<CrosswordEntry> = placed.into_iter().map(|(index, answer, row, column, across)| CrosswordEntry {
+            index,
+            answer,
+            clue: clues[index].clone(),
+            row: (row - minrow) as usize,
+            column: (column - mincolumn) as usize,
+            across,
+            number: 0,
+        }).collect();
+        entries.sort_by_key(|entry| (entry.row, entry.column, !entry.across));
+        let mut number = 0;
+        let mut previous: Option<(usize, usize)> = None;
+        for entry in entries.iter_mut() {
+            if previous != Some((entry.row, entry.column)) {
+                number += 1;
+                previous = Some((entry.row, entry.column));
+            }
+            entry.number = number;
+        }
+        Crossword { width, height, entries }
+    }
+
+    /// Checks whether a word fits at the given position, returns the number of crossings if it
+    /// does. Letters may not touch letters of other words except where they cross.
+    fn fits(cells: &HashMap<(i64,i64), char>, letters: &[char], row: i64, column: i64, across: bool) -> Option<usize> {
+        let position = |i: i64| if across { (row, column + i) } else { (row + i, column) };
+        let neighbours = |(r, c): (i64, i64)| if across { [(r - 1, c), (r + 1, c)] } else { [(r, c - 1), (r, c + 1)] };
+        if cells.contains_key(&position(-1)) || cells.contains_key(&position(letters.len() as i64)) {
+            return None;
+        }
+        let mut crossings = 0;
+        for (i, c) in letters.iter().enumerate() {
+            match cells.get(&position(i as i64)) {
+                Some(existing) if existing == c => crossings += 1,
+                Some(_) => return None,
+                None => {
+                    if neighbours(position(i as i64)).iter().any(|neighbour| cells.contains_key(neighbour)) {
+                        return None;
+                    }
+                }
+            }
+        }
+        if crossings == 0 || crossings == letters.len() {
+            None
+        } else {
+            Some(crossings)
+        }
+    }
+
+    /// Bounding box (min row, max row, min column, max column) of the grid after adding a word
+    fn bounds(cells: &HashMap<(i64,i64), char>, letters: &[char], row: i64, column: i64, across: bool) -> (i64, i64, i64, i64) {
+        let (endrow, endcolumn) = if across { (row, column + letters.len() as i64 - 1) } else { (row + letters.len() as i64 - 1, column) };
+        cells.keys().fold((row, endrow, column, endcolumn), |(minrow, maxrow, mincolumn, maxcolumn), (r, c)| {
+            (minrow.min(*r), maxrow.max(*r), mincolumn.min(*c), maxcolumn.max(*c))
+        })
+    }
+
+    /// Returns the grid, with the letter and the number (if a word starts there) for every cell
+    /// that is part of a word
+    pub fn grid(&self) -> Vec<Vec<CrosswordCell>> {
+        let mut grid: Vec<Vec<CrosswordCell>> = vec![vec![None; self.width]; self.height];
+        for entry in self.entries.iter() {
+            for (i, c) in entry.answer.chars().enumerate() {
+                let (row, column) = entry.cell(i);
+                let number = grid[row][column].and_then(|(_, number)| number);
+                grid[row][column] = Some((c, number));
+            }
+        }
+        for entry in self.entries.iter() {
+            if let Some((c, _)) = grid[entry.row][entry.column] {
+                grid[entry.row][entry.column] = Some((c, Some(entry.number)));
+            }
+        }
+        grid
+    }
+
+    /// Renders the grid as text, letters are shown for the entries that are revealed (pass all
+    /// true for the answer key)
+    pub fn totext(&self, revealed: &[bool]) -> String {
+        let grid = self.grid();
+        let shown = self.shown(revealed);
+        let separator = format!("+{}\n", "---+".repeat(self.width));
+        let mut out = separator.clone();
+        for (row, cells) in grid.iter().enumerate() {
+            out.push('|');
+            for (column, cell) in cells.iter().enumerate() {
+                match cell {
+                    Some((c, number)) => {
+                        let number = number.map(|number| number.to_string()).unwrap_or_default();
+                        let letter = if shown[row][column] { *c } else { ' ' };
+                        out.push_str(&format!("{:<2}{}|", number, letter));
+                    },
+                    None => out.push_str("###|")
+                }
+            }
+            out.push('\n');
+            out.push_str(&separator);
+        }
+        out
+    }
+
+    /// Renders the clues as text
+    pub fn cluestext(&self) -> String {
+        let mut out = String::new();
+        for (label, across) in [("ACROSS", true), ("DOWN", false)].iter() {
+            out.push_str(label);
+            out.push('\n');
+            for entry in self.entries.iter().filter(|entry| entry.across == *across) {
+                out.push_str(&format!("{:>3}. {} ({})\n", entry.number, entry.clue, entry.answer.chars().count()));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Renders the answers as text
+    pub fn answerstext(&self) -> String {
+        let mut out = String::new();
+        for entry in self.entries.iter() {
+            out.push_str(&format!("{:>3} {:<6} {}\n", entry.number, if entry.across { "across" } else { "down" }, entry.answer));
+        }
+        out
+    }
+
+    /// Renders the puzzle (or the answer key) as an SVG image, the clues are listed below the grid
+    pub fn tosvg(&self, solution: bool) -> String {
+        let cellsize = 32;
+        let grid = self.grid();
+        let cluelines: Vec<String> = self.cluestext().lines().map(|line| line.to_string()).collect();
+        let width = (self.width * cellsize + 2).max(400);
+        let height = self.height * cellsize + 20 + cluelines.len() * 18;
+        let mut out = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"sans-serif\">\n", width, height);
+        for (row, cells) in grid.iter().enumerate() {
+            for (column, cell) in cells.iter().enumerate() {
+                if let Some((c, number)) = cell {
+                    let (x, y) = (column * cellsize + 1, row * cellsize + 1);
+                    out.push_str(&format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"white\" stroke=\"black\"/>\n", x, y, cellsize, cellsize));
+                    if let Some(number) = number {
+                        out.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"9\">{}</text>\n", x + 2, y + 10, number));
+                    }
+                    if solution {
+                        out.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"18\" text-anchor=\"middle\">{}</text>\n", x + cellsize / 2, y + cellsize - 8, c));
+                    }
+                }
+            }
+        }
+        for (i, line) in cluelines.iter().enumerate() {
+            out.push_str(&format!("<text x=\"1\" y=\"{}\" font-size=\"14\" xml:space=\"preserve\">{}</text>\n", self.height * cellsize + 20 + i * 18, escapexml(line)));
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    /// Renders the puzzle (or the answer key) as a standalone HTML page
+    pub fn tohtml(&self, title: &str, solution: bool) -> String {
+        let mut out = String::new();
+        out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        out.push_str(&format!("<title>{}</title>\n", escapexml(title)));
+        out.push_str("<style>\ntable.crossword { border-collapse: collapse; }\ntable.crossword td { width: 2em; height: 2em; padding: 0; position: relative; text-align: center; font-size: 1.2em; }\ntable.crossword td.letter { border: 1px solid black; }\ntable.crossword sup { position: absolute; top: 1px; left: 2px; font-size: 0.5em; }\n</style>\n");
+        out.push_str("</head>\n<body>\n");
+        out.push_str(&format!("<h1>{}</h1>\n<table class=\"crossword\">\n", escapexml(title)));
+        for cells in self.grid().iter() {
+            out.push_str("<tr>");
+            for cell in cells.iter() {
+                match cell {
+                    Some((c, number)) => {
+                        let number = number.map(|number| format!("<sup>{}</sup>", number)).unwrap_or_default();
+                        let letter = if solution { c.to_string() } else { String::new() };
+                        out.push_str(&format!("<td class=\"letter\">{}{}</td>", number, letter));
+                    },
+                    None => out.push_str("<td></td>")
+                }
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+        for (label, across) in [("Across", true), ("Down", false)].iter() {
+            out.push_str(&format!("<h2>{}</h2>\n<ul>\n", label));
+            for entry in self.entries.iter().filter(|entry| entry.across == *across) {
+                if solution {
+                    out.push_str(&format!("<li>{}. {} ({}): <b>{}</b></li>\n", entry.number, escapexml(&entry.clue), entry.answer.chars().count(), entry.answer));
+                } else {
+                    out.push_str(&format!("<li>{}. {} ({})</li>\n", entry.number, escapexml(&entry.clue), entry.answer.chars().count()));
+                }
+            }
+            out.push_str("</ul>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    /// Which cells show their letter, given which entries are revealed
+    fn shown(&self, revealed: &[bool]) -> Vec<Vec<bool>> {
+        let mut shown = vec![vec![false; self.width]; self.height];
+        for (entry, _) in self.entries.iter().zip(revealed.iter()).filter(|(_, revealed)| **revealed) {
+            for i in 0..entry.answer.chars().count() {
+                let (row, column) = entry.cell(i);
+                shown[row][column] = true;
+            }
+        }
+        shown
+    }
+}
+
+impl CrosswordEntry {
+    /// The grid position of the i-th letter
+    pub fn cell(&self, i: usize) -> (usize, usize) {
+        if self.across {
+            (self.row, self.column + i)
+        } else {
+            (self.row + i, self.column)
+        }
+    }
+}
+
+impl WordSearch {
+    /// Parses the directions words may run in: a comma separated list of compass directions
+    /// (e, s, se, ne, w, n, nw, sw) or one of the presets easy (e,s), normal (e,s,se,ne) and
+    /// hard (all directions, including backwards)
+    pub fn parsedirections(directions: &str) -> Result<Vec<(i64, i64)>, String> {
+        let directions = match directions {
+            "easy" => "e,s",
+            "normal" => "e,s,se,ne",
+            "hard" => "e,s,se,ne,w,n,nw,sw",
+            directions => directions
+        };
+        directions.split(',').map(|name| {
+            DIRECTIONS.iter().find(|(direction, _, _)| *direction == name.trim().to_lowercase()).map(|(_, rowstep, columnstep)| (*rowstep, *columnstep))
+                .ok_or_else(|| format!("No such direction: {} (expected easy, normal, hard, or a list of: e, s, se, ne, w, n, nw, sw)", name))
+        }).collect()
+    }
+
+    /// Generates a puzzle of the given size from the given items and clues (one for each item),
+    /// hiding at most count words. Words that do not fit are skipped. The remaining cells are
+    /// filled with letters from the alphabet, duplicates in the alphabet make a letter more likely.
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate(items: &[&VocaItem], clues: &[String], count: usize, width: usize, height: usize, directions: &[(i64, i64)], alphabet: &[char], seed: u64) -> WordSearch {
+        let mut rng: StdRng = SeedableRng::from_seed(&[seed as usize][..]);
+        let mut grid: Vec<Vec<Option<char>>> = vec![vec![None; width]; height];
+        let mut order: Vec<usize> = (0..items.len()).filter(|i| {
+            let length = Crossword::normalise(&items[*i].word).chars().count();
+            length > 1 && length <= width.max(height) && width.min(height) > 0
+        }).collect();
+        order.sort_by_key(|i| std::cmp::Reverse(Crossword::normalise(&items[*i].word).chars().count()));
+        let mut entries: Vec<WordSearchEntry> = Vec::new();
+        for index in order {
+            if entries.len() >= count || directions.is_empty() {
+                break;
+            }
+            let answer = Crossword::normalise(&items[index].word);
+            if entries.iter().any(|entry| entry.answer == answer) {
+                continue;
+            }
+            let letters: Vec<char> = answer.chars().collect();
+            let length = letters.len() as i64;
+            //try random positions until the word fits
+            for _ in 0..500 {
+                let direction = directions[rng.gen_range(0, directions.len())];
+                let row = rng.gen_range(0, height) as i64;
+                let column = rng.gen_range(0, width) as i64;
+                let (endrow, endcolumn) = (row + direction.0 * (length - 1), column + direction.1 * (length - 1));
+                if endrow < 0 || endrow >= height as i64 || endcolumn < 0 || endcolumn >= width as i64 {
+                    continue;
+                }
+                let cell = |i: usize| ((row + direction.0 * i as i64) as usize, (column + direction.1 * i as i64) as usize);
+                if letters.iter().enumerate().all(|(i, c)| { let (r, col) = cell(i); grid[r][col].map(|existing| existing == *c).unwrap_or(true) }) {
+                    for (i, c) in letters.iter().enumerate() {
+                        let (r, col) = cell(i);
+                        grid[r][col] = Some(*c);
+                    }
+                    entries.push(WordSearchEntry { index, answer, clue: clues[index].clone(), row: row as usize, column: column as usize, direction });
+                    break;
+                }
+            }
+        }
+        let grid = grid.into_iter().map(|row| row.into_iter().map(|cell| cell.unwrap_or_else(|| {
+            if alphabet.is_empty() { 'X' } else { alphabet[rng.gen_range(0, alphabet.len())] }
+        })).collect()).collect();
+        entries.sort_by_key(|entry| entry.clue.to_lowercase());
+        WordSearch { grid, entries }
+    }
+
+    /// Returns which cells are part of a hidden word
+    pub fn solution(&self) -> Vec<Vec<bool>> {
+        let mut solution: Vec<Vec<bool>> = self.grid.iter().map(|row| vec![false; row.len()]).collect();
+        for entry in self.entries.iter() {
+            for i in 0..entry.answer.chars().count() as i64 {
+                solution[(entry.row as i64 + entry.direction.0 * i) as usize][(entry.column as i64 + entry.direction.1 * i) as usize] = true;
+            }
+        }
+        solution
+    }
+
+    /// Renders the grid as text, the answer key only shows the letters of the hidden words
+    pub fn totext(&self, solution: bool) -> String {
+        let shown = self.solution();
+        let mut out = String::new();
+        for (row, cells) in self.grid.iter().enumerate() {
+            let line: Vec<String> = cells.iter().enumerate().map(|(column, c)| {
+                if !solution || shown[row][column] { c.to_string() } else { ".".to_string() }
+            }).collect();
+            out.push_str(&line.join(" "));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Renders the puzzle (or the answer key) as a standalone HTML page
+    pub fn tohtml(&self, title: &str, solution: bool) -> String {
+        let shown = self.solution();
+        let mut out = String::new();
+        out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        out.push_str(&format!("<title>{}</title>\n", escapexml(title)));
+        out.push_str("<style>\ntable.wordsearch { border-collapse: collapse; font-family: monospace; font-size: 1.4em; }\ntable.wordsearch td { width: 1.6em; height: 1.6em; text-align: center; }\ntable.wordsearch td.found { background: #ffe680; font-weight: bold; }\n</style>\n");
+        out.push_str("</head>\n<body>\n");
+        out.push_str(&format!("<h1>{}</h1>\n<table class=\"wordsearch\">\n", escapexml(title)));
+        for (row, cells) in self.grid.iter().enumerate() {
+            out.push_str("<tr>");
+            for (column, c) in cells.iter().enumerate() {
+                if solution && shown[row][column] {
+                    out.push_str(&format!("<td class=\"found\">{}</td>", escapexml(&c.to_string())));
+                } else {
+                    out.push_str(&format!("<td>{}</td>", escapexml(&c.to_string())));
+                }
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n<h2>Find the words for</h2>\n<ul>\n");
+        for entry in self.entries.iter() {
+            if solution {
+                out.push_str(&format!("<li>{}: <b>{}</b></li>\n", escapexml(&entry.clue), escapexml(&entry.answer)));
+            } else {
+                out.push_str(&format!("<li>{}</li>\n", escapexml(&entry.clue)));
+            }
+        }
+        out.push_str("</ul>\n</body>\n</html>\n");
+        out
+    }
+}
+
+impl SavedGame {
+    /// Load a saved game
+    pub fn load(filename: &str) -> Result<SavedGame, Box<dyn Error>> {
+        let data = fs::read_to_string(filename)?;
+        let data: SavedGame = serde_json::from_str(data.as_str())?; //(shadowing)
+        Ok(data)
+    }
+
+    /// Save a game so it can be resumed
+    pub fn save(&self, filename: &str) -> std::io::Result<()> {
+        let data: String = serde_json::to_string(self)?;
+        fs::write(filename, data)
+    }
+
+    /// Describes the game by the command it was started with (without the program name)
+    pub fn describe(&self) -> String {
+        let args: Vec<&str> = self.args.iter().skip(1).map(|arg| arg.as_str()).collect();
+        format!("vj {}", args.join(" "))
+    }
+}
+
+impl ReviewStats {
+    /// Load the review statistics
+    pub fn load(filename: &str) -> Result<ReviewStats, Box<dyn Error>> {
+        let data = fs::read_to_string(filename)?;
+        let data: ReviewStats = serde_json::from_str(data.as_str())?; //(shadowing)
+        Ok(data)
+    }
+
+    /// Save the review statistics
+    pub fn save(&self, filename: &str) -> std::io::Result<()> {
+        let data: String = serde_json::to_string(self)?;
+        fs::write(filename, data)
+    }
+
+    /// The current day (in days since the epoch, UTC)
+    pub fn today() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).expect("Unable to get time").as_secs() / DAY
+    }
+
+    /// What was reviewed today so far
+    pub fn todaystats(&self) -> DayStats {
+        self.history.get(&ReviewStats::today()).cloned().unwrap_or_default()
+    }
+
+    /// The current streak, which is broken if there were no reviews yesterday or today
+    pub fn currentstreak(&self) -> u32 {
+        if self.lastday + 1 >= ReviewStats::today() {
+            self.streak
+        } else {
+            0
+        }
+    }
+
+    /// Record a review, new is set if the item was never seen before
+    pub fn record(&mut self, new: bool, correct: bool) {
+        let today = ReviewStats::today();
+        if self.lastday != today {
+            self.streak = if self.lastday + 1 == today { self.streak + 1 } else { 1 };
+            self.longeststreak = self.longeststreak.max(self.streak);
+            self.lastday = today;
+        }
+        let daystats = self.history.entry(today).or_default();
+        daystats.reviews += 1;
+        if new {
+            daystats.new += 1;
+        }
+        if correct {
+            daystats.correct += 1;
+        }
+    }
+}
+
+///Copy the value for the given key from one map to another, or remove it if there is none
+fn takeover<T: Clone>(target: &mut HashMap<String,T>, source: &HashMap<String,T>, key: &str) {
+    match source.get(key) {
+        Some(value) => target.insert(key.to_string(), value.clone()),
+        None => target.remove(key)
+    };
+}
+
+/// Checks whether the input matches (one of the alternatives in) the reference translation,
+/// case-insensitive
+pub fn checktranslation(input: &str, reference: &str) -> bool {
+    for candidate in  Regex::new(r"\b[\w\s]+\b").unwrap().find_iter(reference) {
+        let candidate = candidate.as_str().to_lowercase();
+        if candidate == input.to_lowercase() {
+            return true;
+        }
+    }
+    false
+}
+
+/// Removes diacritics from a string (e.g. é becomes e), by decomposing it and dropping the
+/// combining marks
+pub fn stripdiacritics(s: &str) -> String {
+    s.nfd().filter(|c| !is_combining_mark(*c)).nfc().collect()
+}
+
+/// Escapes the characters that are special in XML and HTML
+pub fn escapexml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Is this a valid name for a custom field? (letters, digits and underscores)
+pub fn isfieldname(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Levenshtein edit distance between two strings (in characters)
+pub fn editdistance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current: Vec<usize> = vec!(i + 1);
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current.push(*[substitution, previous[j+1] + 1, current[j] + 1].iter().min().unwrap());
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Similarity between two strings based on the edit distance (0.0 - 1.0)
+pub fn stringsimilarity(a: &str, b: &str) -> f64 {
+    let length = std::cmp::max(a.chars().count(), b.chars().count());
+    if length == 0 {
+        1.0
+    } else {
+        1.0 - editdistance(a, b) as f64 / length as f64
+    }
+}
+
+impl CardTemplate {
+    /// Load a template file (JSON)
+    pub fn load(filename: &str) -> Result<CardTemplate, Box<dyn Error>> {
+        let data = fs::read_to_string(filename)?;
+        let data: CardTemplate = serde_json::from_str(data.as_str())?; //(shadowing)
+        Ok(data)
+    }
+
+    /// Render the front of the card for an item
+    pub fn front(&self, item: &VocaItem) -> String {
+        rendertemplate(&self.front, item, "")
+    }
+
+    /// Render the back of the card for an item
+    pub fn back(&self, item: &VocaItem) -> String {
+        rendertemplate(&self.back, item, &self.front(item))
+    }
+}
+
+/// Render a card template for an item
+pub fn rendertemplate(template: &str, item: &VocaItem, frontside: &str) -> String {
+    rendersection(template, item, frontside).trim_end().to_string()
+}
+
+/// Render (part of) a card template, whitespace is kept as is
+fn rendersection(template: &str, item: &VocaItem, frontside: &str) -> String {
+    let mut output = String::new();
+    let mut remainder = template;
+    while let Some(begin) = remainder.find("{{") {
+        output.push_str(&remainder[..begin]);
+        let tag_end = match remainder[begin..].find("}}") {
+            Some(end) => begin + end,
+            None => { //unterminated tag, output literally
+                output.push_str(&remainder[begin..]);
+                return output;
+            }
+        };
+        let tag = remainder[begin+2..tag_end].trim();
+        remainder = &remainder[tag_end+2..];
+        if let Some(name) = tag.strip_prefix('#').or_else(|| tag.strip_prefix('^')) {
+            //section, find the matching closing tag (sections with the same name may be nested)
+            let (open, close) = (format!("{{{{{}}}}}", tag), format!("{{{{/{}}}}}", name));
+            let (mut depth, mut position, mut section_end) = (1, 0, None);
+            while let Some(next) = remainder[position..].find(&close) {
+                depth += remainder[position..position+next].matches(&open).count();
+                depth -= 1;
+                if depth == 0 {
+                    section_end = Some(position + next);
+                    break;
+                }
+                position += next + close.len();
+            }
+            let section_end = section_end.unwrap_or(remainder.len());
+            let nonempty = match name {
+                "FrontSide" => !frontside.is_empty(),
+                _ => !item.field(name).unwrap_or_default().is_empty()
+            };
+            if nonempty == tag.starts_with('#') {
+                output.push_str(&rendersection(&remainder[..section_end], item, frontside));
+            }
+            remainder = &remainder[std::cmp::min(section_end + close.len(), remainder.len())..];
+        } else if tag == "FrontSide" {
+            output.push_str(frontside);
+        } else if !tag.starts_with('/') {
+            output.push_str(&item.field(tag).unwrap_or_default());
+        }
+    }
+    output.push_str(remainder);
+    output
+}
+
+/// Return the user's card template file
+pub fn defaulttemplatefile() -> PathBuf {
+    dirs::config_dir().expect("Unable to find configuration dir").join("vocajeux").join("template.json")
+}
+
+/// Returns the card template to use for a list: the list's own template, or else the user's
+/// template (if any)
+pub fn gettemplate(data: &VocaList) -> Option<CardTemplate> {
+    if let Some(ref template) = data.template {
+        Some(template.clone())
+    } else {
+        let templatefile = defaulttemplatefile();
+        if templatefile.exists() {
+            match CardTemplate::load(templatefile.to_str().expect("Invalid template file")) {
+                Ok(template) => Some(template),
+                Err(err) => {
+                    eprintln!("Unable to load template {}: {}", templatefile.to_str().unwrap(), err);
+                    None
+                }
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// Return the default data directory
+pub fn defaultdatadir() -> PathBuf {
+    dirs::config_dir().expect("Unable to find configuration dir").join("vocajeux").join("data")
+}
+///
+/// Return the default score directory
+pub fn defaultscoredir() -> PathBuf {
+    dirs::config_dir().expect("Unable to find configuration dir").join("vocajeux").join("scores")
+}
+
+pub fn getdatafile(name: &str, datapath: PathBuf) -> Option<PathBuf> {
+    let mut filename: String = name.to_owned();
+    filename.push_str(".json");
+    let datafile = datapath.join(filename);
+    match datafile.exists() {
+        true => Some(datafile),
+        false => None
+    }
+}
+
+/// Return the file with the review statistics, these are kept per user (i.e. per access key)
+/// rather than per list
+pub fn getstatsfile(scorepath: PathBuf, accesskey: Option<&str>) -> PathBuf {
+    match accesskey {
+        Some(accesskey) => scorepath.join(format!("stats.{}.json", accesskey)),
+        None => scorepath.join("stats.json")
+    }
+}
+
+/// Return the file an interrupted game is saved in, there is only one per user (and access key)
+pub fn getsavedgamefile(scorepath: PathBuf, accesskey: Option<&str>) -> PathBuf {
+    match accesskey {
+        Some(accesskey) => scorepath.join(format!("session.{}.json", accesskey)),
+        None => scorepath.join("session.json")
+    }
+}
+
+/// Return all vocabulary lists (JSON files) in the data directory, in alphabetical order
+pub fn getdatafiles(datapath: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match datapath.read_dir() {
+        Ok(dir) => dir.flatten().map(|file| file.path()).filter(|path| path.extension().map(|extension| extension == "json").unwrap_or(false)).collect(),
+        Err(_) => Vec::new()
+    };
+    files.sort();
+    files
+}
+
+/// Return the score file, scores are kept separately per access key and per pair of prompt and
+/// answer fields (except for the default pair)
+pub fn getscorefile(name: &str, scorepath: PathBuf, accesskey: Option<&str>, fields: Option<&FieldPair>) -> PathBuf {
+    let mut filename: String = name.strip_suffix(".json").unwrap_or(name).to_string();
+    if let Some(accesskey) = accesskey {
+        filename.push('.');
+        filename.push_str(accesskey);
+    }
+    if let Some(fields) = fields {
+        if !fields.is_default() {
+            filename.push('.');
+            filename.push_str(&fields.to_string());
+        }
+    }
+    filename.push_str(".score.json");
+    scorepath.join(filename)
+}
+
+/// Return the file an exam record is stored in, exams are stored alongside the score files but
+/// every exam gets its own file
+pub fn getexamfile(name: &str, scorepath: PathBuf, accesskey: Option<&str>, started: u64) -> PathBuf {
+    let mut filename: String = name.strip_suffix(".json").unwrap_or(name).to_string();
+    if let Some(accesskey) = accesskey {
+        filename.push('.');
+        filename.push_str(accesskey);
+    }
+    filename.push_str(&format!(".exam.{}.json", started));
+    scorepath.join(filename)
+}
+
+
+/// Returns an index of available vocabulary sets
+pub fn getdataindex(configpath_opt: Option<PathBuf>) -> Vec<PathBuf> {
+    let mut index: Vec<PathBuf> = Vec::new();
+    let configpath;
+    if let Some(configpath_some) = configpath_opt {
+        configpath = configpath_some;
+    } else {
+        configpath = dirs::config_dir().expect("Unable to find configuration dir");
+    }
+    let datapath = configpath.join("vocajeux").join("data");
+    if datapath.exists() {
+        for file in datapath.read_dir().expect("Unable to read dir").flatten() {
+            index.push(file.path());
+        }
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(word: &str, translation: &str, example: &str) -> VocaItem {
+        VocaItem {
+            word: word.to_string(),
+            transcription: String::new(),
+            translation: translation.to_string(),
+            example: example.to_string(),
+            comment: String::new(),
+            tags: Vec::new(),
+            extra: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn rendertemplate_fields() {
+        let item = item("chat", "cat", "");
+        assert_eq!(rendertemplate("{{word}} - {{ translation }}", &item, ""), "chat - cat");
+        assert_eq!(rendertemplate("{{FrontSide}}: {{translation}}", &item, "chat"), "chat: cat");
+    }
+
+    #[test]
+    fn rendertemplate_sections() {
+        let item = item("chat", "cat", "");
+        assert_eq!(rendertemplate("{{word}}{{#translation}} ({{translation}}){{/translation}}", &item, ""), "chat (cat)");
+        assert_eq!(rendertemplate("{{word}}{{#example}} ({{example}}){{/example}}", &item, ""), "chat");
+        assert_eq!(rendertemplate("{{^example}}no example{{/example}}", &item, ""), "no example");
+        //whitespace inside sections is kept, only the end of the card is trimmed
+        assert_eq!(rendertemplate("{{#word}}{{word}} {{/word}}{{translation}}\n", &item, ""), "chat cat");
+    }
+
+    #[test]
+    fn rendertemplate_unterminated() {
+        let item = item("chat", "cat", "");
+        assert_eq!(rendertemplate("a {{b", &item, ""), "a {{b");
+        assert_eq!(rendertemplate("{{word}} {{translation", &item, ""), "chat {{translation");
+    }
+
+    #[test]
+    fn wordsearch_hides_words() {
+        let items = [item("chat", "cat", ""), item("chien", "dog", ""), item("oiseau", "bird", "")];
+        let items: Vec<&VocaItem> = items.iter().collect();
+        let clues: Vec<String> = items.iter().map(|item| item.translation.clone()).collect();
+        let directions = WordSearch::parsedirections("hard").unwrap();
+        let puzzle = WordSearch::generate(&items, &clues, 3, 8, 8, &directions, &['a', 'b'], 42);
+        assert_eq!(puzzle.entries.len(), 3);
+        assert_eq!(puzzle.grid.len(), 8);
+        for entry in puzzle.entries.iter() {
+            let word: String = (0..entry.answer.chars().count() as i64).map(|i| {
+                puzzle.grid[(entry.row as i64 + entry.direction.0 * i) as usize][(entry.column as i64 + entry.direction.1 * i) as usize]
+            }).collect();
+            assert_eq!(word, entry.answer);
+        }
+        //the same seed gives the same puzzle
+        let again = WordSearch::generate(&items, &clues, 3, 8, 8, &directions, &['a', 'b'], 42);
+        assert_eq!(puzzle.grid, again.grid);
+    }
+
+    #[test]
+    fn wordsearch_empty_grid() {
+        let items = [item("chat", "cat", "")];
+        let items: Vec<&VocaItem> = items.iter().collect();
+        let clues = vec!["cat".to_string()];
+        let directions = WordSearch::parsedirections("easy").unwrap();
+        assert!(WordSearch::generate(&items, &clues, 1, 0, 0, &directions, &[], 1).entries.is_empty());
+        assert!(WordSearch::generate(&items, &clues, 1, 0, 10, &directions, &[], 1).entries.is_empty());
+    }
+
+    #[test]
+    fn wordsearch_directions() {
+        assert_eq!(WordSearch::parsedirections("easy").unwrap(), vec![(0, 1), (1, 0)]);
+        assert_eq!(WordSearch::parsedirections("s, NE").unwrap(), vec![(1, 0), (-1, 1)]);
+        assert!(WordSearch::parsedirections("up").is_err());
+    }
+
+    fn list(items: Vec<VocaItem>) -> VocaList {
+        VocaList { items, template: None, weights: HashMap::new(), sources: HashMap::new() }
+    }
+
+    #[test]
+    fn merge_lists() {
+        let first = list(vec![item("chat", "cat", ""), item("chien", "dog", "")]);
+        let second = list(vec![item("chat", "chat (talk)", ""), item("oiseau", "bird", "")]);
+        let merged = VocaList::merge(vec![(first, 1.0), (second, 2.0)]);
+        let words: Vec<&str> = merged.items.iter().map(|item| item.word.as_str()).collect();
+        assert_eq!(words, vec!["chat", "chien", "oiseau"]);
+        //a word in both lists belongs to the first, along with its weight
+        assert_eq!(merged.items[0].translation, "cat");
+        assert_eq!(merged.source(&merged.items[0]), 0);
+        assert_eq!(merged.source(&merged.items[2]), 1);
+        assert_eq!(merged.weight(&merged.items[0]), 1.0);
+        assert_eq!(merged.weight(&merged.items[2]), 2.0);
+    }
+
+    #[test]
+    fn merge_and_update_scores() {
+        let first = list(vec![item("chat", "cat", ""), item("chien", "dog", "")]);
+        let second = list(vec![item("chat", "chat (talk)", ""), item("oiseau", "bird", "")]);
+        let merged = VocaList::merge(vec![(first, 1.0), (second, 1.0)]);
+        let (chat, oiseau) = (&merged.items[0], &merged.items[2]);
+        let mut firstscores = VocaScore::default();
+        firstscores.addscore(chat, true);
+        let mut secondscores = VocaScore::default();
+        secondscores.addscore(chat, false);
+        secondscores.addscore(oiseau, false);
+        let mut scores = VocaScore::default();
+        scores.merge(firstscores, &merged, 0);
+        scores.merge(secondscores, &merged, 1);
+        assert_eq!(scores.correct.get(&chat.id_as_string()), Some(&1));
+        assert_eq!(scores.incorrect.get(&chat.id_as_string()), None);
+        assert_eq!(scores.incorrect.get(&oiseau.id_as_string()), Some(&1));
+
+        scores.addscore(chat, true);
+        scores.addscore(oiseau, true);
+        let mut firstscores = VocaScore::default();
+        firstscores.update(&scores, &merged, 0);
+        let mut secondscores = VocaScore::default();
+        secondscores.addscore(chat, false);
+        secondscores.update(&scores, &merged, 1);
+        assert_eq!(firstscores.correct.get(&chat.id_as_string()), Some(&2));
+        assert_eq!(firstscores.correct.get(&oiseau.id_as_string()), None);
+        //the second list's own scores for the shared word are left alone
+        assert_eq!(secondscores.correct.get(&chat.id_as_string()), None);
+        assert_eq!(secondscores.incorrect.get(&chat.id_as_string()), Some(&1));
+        assert_eq!(secondscores.correct.get(&oiseau.id_as_string()), Some(&1));
+    }
+
+    #[test]
+    fn excluded_items_are_not_picked() {
+        let data = list(vec![item("chat", "cat", ""), item("chien", "dog", ""), item("oiseau", "bird", ""), item("souris", "mouse", "")]);
+        let mut scores = VocaScore::default();
+        scores.suspend(&data.items[0]);
+        scores.bury(&data.items[1], u64::MAX);
+        scores.markknown(&data.items[2]);
+        let available = data.available(Some(&scores), None, |_| true);
+        assert_eq!(available.len(), 1);
+        assert_eq!(available[0].word, "souris");
+        for _ in 0..10 {
+            assert_eq!(data.pick(Some(&mut scores), None, false).map(|item| item.word.as_str()), Some("souris"));
+        }
+        assert!(data.pickwith(Some(&mut scores), None, false, |item| item.word != "souris").is_none());
+        //buried items come back when the time has passed
+        scores.bury(&data.items[1], 0);
+        assert_eq!(data.available(Some(&scores), None, |_| true).len(), 2);
+        assert_eq!(data.available(None, None, |_| true).len(), 4);
+    }
+
+    #[test]
+    fn pick_distinct_items() {
+        let data = list(vec![item("chat", "cat", ""), item("chien", "dog", ""), item("oiseau", "bird", ""), item("chat", "cat", "")]);
+        let picked = data.pickdistinct(None, None, 10, false, |item| item.word != "oiseau");
+        assert_eq!(picked.len(), 2);
+        assert_ne!(picked[0].word, picked[1].word);
+        assert_eq!(data.pickdistinct(None, None, 1, false, |_| true).len(), 1);
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let (chat, chien) = (item("chat", "cat", ""), item("chien", "dog", ""));
+        let mut scores = VocaScore::default();
+        scores.addgrade(&chien, Grade::Good);
+        let before = scores.snapshot(&chat);
+        scores.addgrade(&chat, Grade::Easy);
+        scores.addconfusion(&chat, &chien);
+        scores.updateitem(&before, &chat);
+        let id = chat.id_as_string();
+        assert!(!scores.lastseen.contains_key(&id));
+        assert!(!scores.correct.contains_key(&id));
+        assert!(!scores.due.contains_key(&id));
+        assert!(!scores.confusions.contains_key(&id));
+        //other items are not affected
+        assert_eq!(scores.correct.get(&chien.id_as_string()), Some(&1));
+    }
+
+    #[test]
+    fn crossword_entries_cross() {
+        let items = [item("maison", "house", ""), item("soleil", "sun", ""), item("ami", "friend", ""), item("xyz", "", "")];
+        let items: Vec<&VocaItem> = items.iter().collect();
+        let clues: Vec<String> = items.iter().map(|item| item.translation.clone()).collect();
+        let crossword = Crossword::generate(&items, &clues, 10);
+        //xyz shares no letter with any other word, so it can not be placed
+        assert!(crossword.entries.iter().all(|entry| entry.answer != "XYZ"));
+        assert_eq!(crossword.entries.len(), 3);
+        let grid = crossword.grid();
+        let mut cells: HashMap<(usize,usize), usize> = HashMap::new();
+        for entry in crossword.entries.iter() {
+            assert_eq!(entry.clue, clues[entry.index]);
+            assert_eq!(entry.answer, Crossword::normalise(&items[entry.index].word));
+            for (i, letter) in entry.answer.chars().enumerate() {
+                let (row, column) = entry.cell(i);
+                assert_eq!(grid[row][column].map(|(c, _)| c), Some(letter));
+                *cells.entry((row, column)).or_insert(0) += 1;
+            }
+        }
+        //every word after the first crosses another one
+        for entry in crossword.entries.iter().skip(1) {
+            assert!((0..entry.answer.chars().count()).any(|i| cells[&entry.cell(i)] > 1));
+        }
+        assert_eq!(Crossword::generate(&items, &clues, 2).entries.len(), 2);
+    }
+
+    #[test]
+    fn grade_parse() {
+        assert_eq!(Grade::parse("1"), Some(Grade::Again));
+        assert_eq!(Grade::parse("i"), Some(Grade::Again));
+        assert_eq!(Grade::parse("Hard"), Some(Grade::Hard));
+        assert_eq!(Grade::parse("k"), Some(Grade::Good));
+        assert_eq!(Grade::parse("GOOD"), Some(Grade::Good));
+        //the exclusion commands are not grades
+        assert_eq!(Grade::parse("K"), None);
+        assert_eq!(Grade::parse("S"), None);
+        assert_eq!(Grade::parse("B"), None);
+        assert_eq!(Grade::parse("4"), Some(Grade::Easy));
+        assert_eq!(Grade::parse("5"), None);
+        assert_eq!(Grade::parse(""), None);
+        assert!(!Grade::Again.correct());
+        assert!(Grade::Hard.correct());
+    }
+
+    #[test]
+    fn cloze() {
+        assert_eq!(item("avoir", "", "Il {{a}} faim").cloze(), Some(("Il _____ faim".to_string(), "a".to_string())));
+        assert_eq!(item("chat", "", "Le Chat dort.").cloze(), Some(("Le _____ dort.".to_string(), "Chat".to_string())));
+        //an inflected form that shares the stem
+        assert_eq!(item("manger", "", "Nous mangeons ensemble").cloze(), Some(("Nous _____ ensemble".to_string(), "mangeons".to_string())));
+        assert_eq!(item("chien", "", "Le chat dort.").cloze(), None);
+        assert_eq!(item("chien", "", "").cloze(), None);
+    }
+
+    #[test]
+    fn reviewstats_streak() {
+        let today = ReviewStats::today();
+        let mut stats = ReviewStats::default();
+        assert_eq!(stats.currentstreak(), 0);
+        stats.record(true, true);
+        stats.record(false, false);
+        assert_eq!(stats.currentstreak(), 1);
+        let daystats = stats.todaystats();
+        assert_eq!((daystats.reviews, daystats.new, daystats.correct), (2, 1, 1));
+        //continued from yesterday
+        let mut stats = ReviewStats { streak: 3, longeststreak: 3, lastday: today - 1, ..Default::default() };
+        assert_eq!(stats.currentstreak(), 3);
+        stats.record(false, true);
+        assert_eq!((stats.streak, stats.longeststreak, stats.lastday), (4, 4, today));
+        //broken streak
+        let mut stats = ReviewStats { streak: 5, longeststreak: 7, lastday: today - 2, ..Default::default() };
+        assert_eq!(stats.currentstreak(), 0);
+        stats.record(false, true);
+        assert_eq!((stats.streak, stats.longeststreak), (1, 7));
     }
-    index
 }